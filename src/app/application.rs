@@ -4,30 +4,29 @@ use std::sync::Arc;
 use tui::backend::Backend;
 
 use super::config::Config;
+use super::device;
 use super::events::{Event, EventHandler};
+use super::logger::Logger;
+use super::snapshot;
 use super::ui;
 use crate::{Error, Result};
 
-pub fn init(config: Arc<Config>) -> Result<Application<impl Backend>> {
-    let manager = battery::Manager::new()?;
+pub fn init(config: Arc<Config>, logger: &'static Logger) -> Result<Application<impl Backend>> {
+    let manager = device::manager()?;
+
+    let devices = device::selected(&manager, config.batteries())?;
+    // Tab for the battery matching the first selector is opened on start
+    let selected = device::initial(&devices, config.batteries());
 
     // This vec will be used for UI data pre-population before the first tick
-    let batteries = manager
-        .batteries()?
-        .flatten()
-        .map(|battery| ui::View::new(config.clone(), battery))
+    let batteries = devices
+        .into_iter()
+        .map(|device| ui::View::new(config.clone(), device))
         .collect::<Vec<_>>();
 
-    // Probing if any batteries are installed at all
-    if batteries.is_empty() {
-        error!("Unable to find any batteries in system, exiting");
-        return Err(Error::NoBatteries);
-    } else {
-        trace!("Found {} batteries during initialization", batteries.len());
-    }
-
-    let events = EventHandler::from_config(&config);
-    let interface = ui::init(config.clone(), batteries)?;
+    let events = EventHandler::from_config(&config)?;
+    let mut interface = ui::init(config.clone(), logger, batteries)?;
+    interface.tabs_mut().select(selected);
 
     Ok(Application {
         manager,
@@ -46,29 +45,115 @@ pub struct Application<B: Backend> {
 
 impl<B: Backend> Application<B> {
     pub fn run(&mut self) -> Result<()> {
+        let mut redraw = true;
         loop {
-            self.interface.draw()?;
-            self.handle_event()?;
+            if redraw {
+                self.interface.draw()?;
+            }
+            redraw = self.handle_event()?;
         }
     }
 
-    fn handle_event(&mut self) -> Result<()> {
-        match self.events.next()? {
+    /// Handle next event, returning whether interface should be redrawn after it
+    fn handle_event(&mut self) -> Result<bool> {
+        let event = self.events.next()?;
+        match event {
             Event::Exit => Err(Error::UserExit),
             Event::PreviousTab => {
                 self.interface.tabs_mut().previous();
-                Ok(())
+                Ok(true)
             }
             Event::NextTab => {
                 self.interface.tabs_mut().next();
-                Ok(())
+                Ok(true)
+            }
+            Event::Pause => {
+                self.interface.toggle_pause();
+                Ok(true)
+            }
+            Event::CursorBack => {
+                self.interface.cursor_back();
+                Ok(true)
+            }
+            Event::CursorForward => {
+                self.interface.cursor_forward();
+                Ok(true)
+            }
+            Event::ZoomIn => {
+                self.interface.zoom_in();
+                Ok(true)
+            }
+            Event::ZoomOut => {
+                self.interface.zoom_out();
+                Ok(true)
+            }
+            Event::MeanLine => {
+                self.interface.toggle_mean_line();
+                Ok(true)
+            }
+            Event::Sessions => {
+                self.interface.toggle_panel(ui::Panel::Sessions);
+                Ok(true)
+            }
+            Event::Health => {
+                self.interface.toggle_panel(ui::Panel::Health);
+                Ok(true)
             }
-            Event::Tick => {
+            Event::Log => {
+                self.interface.toggle_panel(ui::Panel::Log);
+                Ok(true)
+            }
+            Event::Help => {
+                self.interface.toggle_help();
+                Ok(true)
+            }
+            Event::LineUp => {
+                self.interface.scroll_log(true);
+                Ok(true)
+            }
+            Event::LineDown => {
+                self.interface.scroll_log(false);
+                Ok(true)
+            }
+            Event::Click(x, y) => {
+                self.interface.click(x, y);
+                Ok(true)
+            }
+            Event::Drag(x, y) => {
+                self.interface.drag(x, y);
+                Ok(true)
+            }
+            Event::ScrollUp(x, y) => {
+                self.interface.scroll(x, y, true);
+                Ok(true)
+            }
+            Event::ScrollDown(x, y) => {
+                self.interface.scroll(x, y, false);
+                Ok(true)
+            }
+            Event::Tick if self.interface.is_paused() => {
+                trace!("Updates are paused, ignoring tick");
+                Ok(false)
+            }
+            // Refresh is requested by user explicitly, so it is done even if updates are paused
+            // and failed batteries are retried right away
+            Event::Tick | Event::Refresh => {
+                let force = event == Event::Refresh;
                 for view in self.interface.views_mut() {
-                    view.update(&mut self.manager)?;
+                    view.update(&mut self.manager, force)?;
+                }
+                Ok(true)
+            }
+            // Failed snapshot is not a reason to stop monitoring
+            Event::Snapshot => {
+                match snapshot::write(self.interface.views()) {
+                    Ok(path) => info!("Snapshot was saved into {}", path.display()),
+                    Err(e) => error!("Unable to save snapshot: {}", e),
                 }
-                Ok(())
+                Ok(false)
             }
+            Event::Resize => Ok(true),
+            Event::Redraw => self.interface.is_resized(),
         }
     }
 }
@@ -0,0 +1,176 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use super::device::{self, Device};
+use super::field::{Field, SAMPLE_FIELDS};
+use super::sample::Sample;
+use super::store::Store;
+use super::time;
+use super::Config;
+use crate::{Error, Result, ResultExt};
+
+/// Fields printed by `watch` command when none were requested explicitly
+const DEFAULT_WATCH_FIELDS: &[Field] = &[Field::State, Field::StateOfCharge, Field::EnergyRate, Field::Voltage];
+
+/// Print all batteries installed, one per line
+pub fn list(config: &Config) -> Result<()> {
+    let manager = device::manager()?;
+    let devices = device::selected(&manager, config.batteries())?;
+    let now = SystemTime::now();
+    let mut stdout = io::stdout();
+
+    writeln!(
+        stdout,
+        "{:<5} {:<8} {:<16} {:<20} {:<16} {:<12} {:>7}",
+        "INDEX", "NAME", "VENDOR", "MODEL", "SERIAL", "STATE", "SOC"
+    )?;
+    for device in devices.iter() {
+        let battery = device.battery();
+        let sample = Sample::new(now, battery);
+        let value = |field: Field| {
+            field
+                .value(battery, &sample, config)
+                .unwrap_or_else(|| "N/A".to_string())
+        };
+
+        writeln!(
+            stdout,
+            "{:<5} {:<8} {:<16} {:<20} {:<16} {:<12} {:>7}",
+            device.index(),
+            device.name().unwrap_or("N/A"),
+            value(Field::Vendor),
+            value(Field::Model),
+            value(Field::Serial),
+            value(Field::State),
+            value(Field::StateOfCharge),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Print one `field` value of the battery matching `selector`
+pub fn get(config: &Config, selector: &str, field: Field) -> Result<()> {
+    let manager = device::manager()?;
+    let device = device::find(&manager, selector)?;
+    let sample = Sample::new(SystemTime::now(), device.battery());
+
+    match field.value(device.battery(), &sample, config) {
+        Some(value) => {
+            writeln!(io::stdout(), "{}", value)?;
+            Ok(())
+        }
+        None => Err(Error::Unavailable(field.to_string())),
+    }
+}
+
+/// Print requested `fields` of all batteries as tab-separated values every `--delay` seconds
+pub fn watch(config: &Config, fields: &[Field]) -> Result<()> {
+    let fields = if fields.is_empty() {
+        DEFAULT_WATCH_FIELDS
+    } else {
+        fields
+    };
+    let mut manager = device::manager()?;
+    let mut devices = device::selected(&manager, config.batteries())?;
+
+    match watch_loop(config, &mut manager, &mut devices, fields) {
+        // Output was closed by reader, e.g. `battop watch | head`, which is a normal exit for this command
+        Err(Error::Io(ref e)) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+fn watch_loop(config: &Config, manager: &mut battery::Manager, devices: &mut [Device], fields: &[Field]) -> Result<()> {
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+
+    let header = fields.iter().map(Field::to_string).collect::<Vec<_>>();
+    writeln!(stdout, "timestamp\tbattery\t{}", header.join("\t"))?;
+
+    loop {
+        let now = SystemTime::now();
+        for device in devices.iter_mut() {
+            let id = device.id();
+            manager
+                .refresh(device.battery_mut())
+                .context(|| format!("Unable to refresh battery {}", id))?;
+            let sample = Sample::new(now, device.battery());
+            let values = fields
+                .iter()
+                .map(|field| field.value(device.battery(), &sample, config).unwrap_or_default())
+                .collect::<Vec<_>>();
+
+            writeln!(
+                stdout,
+                "{}\t{}\t{}",
+                humantime::format_rfc3339_seconds(now),
+                device.id(),
+                values.join("\t")
+            )?;
+        }
+        stdout.flush()?;
+
+        thread::sleep(*config.delay());
+    }
+}
+
+/// Export stored samples as CSV for all batteries or only for the one matching `selector`
+pub fn export(config: &Config, selector: Option<&str>, since: Option<Duration>, output: Option<&Path>) -> Result<()> {
+    let manager = device::manager()?;
+    let devices = match selector {
+        Some(selector) => vec![device::find(&manager, selector)?],
+        None => device::selected(&manager, config.batteries())?,
+    };
+    let since = since.map(|since| time::earlier(SystemTime::now(), since));
+
+    let mut writer: Box<dyn Write> = match output {
+        Some(path) => {
+            let file = File::create(path).context(|| format!("Unable to create {}", path.display()))?;
+            Box::new(io::BufWriter::new(file))
+        }
+        None => Box::new(io::BufWriter::new(io::stdout())),
+    };
+
+    let header = SAMPLE_FIELDS.iter().map(Field::to_string).collect::<Vec<_>>();
+    writeln!(writer, "battery,timestamp,{}", header.join(","))?;
+
+    for device in devices.iter() {
+        let store = match Store::existing(device)? {
+            Some(store) => store,
+            None => {
+                debug!("There are no stored samples for battery {}", device.id());
+                continue;
+            }
+        };
+        let samples = match since {
+            Some(since) => store.load_since(since)?,
+            None => store.samples()?.collect(),
+        };
+        debug!("Exporting {} samples from {}", samples.len(), store.path().display());
+
+        for sample in samples.iter() {
+            let values = SAMPLE_FIELDS
+                .iter()
+                .map(|field| field.value(device.battery(), sample, config).unwrap_or_default())
+                .collect::<Vec<_>>();
+            writeln!(
+                writer,
+                "{},{},{}",
+                device.id(),
+                humantime::format_rfc3339_seconds(sample.timestamp),
+                values.join(",")
+            )?;
+        }
+    }
+    writer.flush()?;
+
+    if let Some(path) = output {
+        info!("Samples were exported into {}", path.display());
+    }
+
+    Ok(())
+}
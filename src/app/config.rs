@@ -1,8 +1,15 @@
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::time::Duration;
 use std::u64;
 
-use crate::app::ui::Units;
+use crate::app::field::Field;
+use crate::app::filter::FilterType;
+use crate::app::report::ReportFormat;
+use crate::app::ui::{ChartType, CurrentUnit, EnergyUnit, PowerUnit, TemperatureUnit, Units};
+
+/// Charts displayed when none were requested explicitly
+const DEFAULT_CHARTS: &[ChartType] = &[ChartType::Voltage, ChartType::EnergyRate, ChartType::Temperature];
 
 fn parse_duration(raw: &str) -> Result<Duration, String> {
     match u64::from_str(raw) {
@@ -11,6 +18,44 @@ fn parse_duration(raw: &str) -> Result<Duration, String> {
     }
 }
 
+fn parse_percent(raw: &str) -> Result<f64, String> {
+    match f64::from_str(raw) {
+        Ok(value) if value > 0.0 && value <= 100.0 => Ok(value),
+        _ => Err(format!("{} isn't a percent value in (0; 100] range", raw)),
+    }
+}
+
+fn parse_interval(raw: &str) -> Result<Duration, String> {
+    parse_duration_at_least(raw, Duration::from_millis(1))
+}
+
+fn parse_alias(raw: &str) -> Result<(String, String), String> {
+    let mut parts = raw.splitn(2, '=');
+    match (parts.next(), parts.next()) {
+        (Some(serial), Some(name)) if !serial.trim().is_empty() && !name.trim().is_empty() => {
+            Ok((serial.trim().to_string(), name.trim().to_string()))
+        }
+        _ => Err(format!("{} isn't in SERIAL=NAME form", raw)),
+    }
+}
+
+fn parse_human_duration(raw: &str) -> Result<Duration, String> {
+    parse_duration_at_least(raw, Duration::from_secs(1))
+}
+
+/// Parse duration in a human-readable form, e.g. "90s" or "10m", which is not shorter than `minimum`
+fn parse_duration_at_least(raw: &str, minimum: Duration) -> Result<Duration, String> {
+    match humantime::parse_duration(raw) {
+        Ok(duration) if duration >= minimum => Ok(duration),
+        Ok(_) => Err(format!(
+            "{} should be at least {} long",
+            raw,
+            humantime::format_duration(minimum)
+        )),
+        Err(e) => Err(format!("{} isn't a valid duration: {}", raw, e)),
+    }
+}
+
 /// Interactive batteries viewer.
 ///
 /// The following commands are supported while in battop:
@@ -19,31 +64,258 @@ fn parse_duration(raw: &str) -> Result<Duration, String> {
 ///
 /// * Left: move to previous tab
 ///
+/// * P, Space: pause or resume updates
+///
+/// * Comma, Period: move the chart cursor back or forward in history
+///
+/// * Plus, Minus: zoom charts time window in or out
+///
+/// * M: show or hide charts mean value line
+///
+/// * S: show or hide charge and discharge sessions instead of charts
+///
+/// * H: show or hide battery health trend instead of charts
+///
+/// * R: refresh batteries data right now
+///
+/// * L: show or hide log records instead of charts, Up and Down scroll them
+///
+/// * ?: show or hide help
+///
+/// * Mouse: click tab to select it, scroll wheel over charts to zoom, click or drag over charts to move the cursor
+///
 /// * Q, Ctrl+C, Esc: close viewer
 #[derive(StructOpt, Debug)]
 pub struct Config {
     #[structopt(short = "v", long = "verbose", max_values = 5, parse(from_occurrences))]
     /// Verbosity level, might be repeated up to 5 times (-vvvvv).
-    /// Log is accessible from the stderr.
+    /// Log is accessible from the stderr, from the log file (see `--log-file`)
+    /// or from the log pane while in the interactive viewer
     verbose: usize,
 
+    #[structopt(long = "log-file", parse(from_os_str), raw(global = "true"))]
+    /// Append log records to this file, including the ones produced while interactive viewer is displayed
+    log_file: Option<PathBuf>,
+
     #[structopt(
         short = "d",
         long = "delay",
         default_value = "1",
-        parse(try_from_str = "parse_duration")
+        parse(try_from_str = "parse_duration"),
+        raw(global = "true")
     )]
     /// Delay between updates, in seconds
     delay: Duration,
 
+    #[structopt(
+        long = "redraw-interval",
+        default_value = "100ms",
+        parse(try_from_str = "parse_interval")
+    )]
+    /// How often terminal is checked for resize to redraw the interface, e.g. "100ms".
+    /// Interface is redrawn on input and updates regardless of it
+    redraw_interval: Duration,
+
+    #[structopt(
+        short = "H",
+        long = "history",
+        default_value = "10m",
+        parse(try_from_str = "parse_human_duration")
+    )]
+    /// How long charts history is kept, e.g. "90s", "10m" or "1h"
+    history: Duration,
+
     #[structopt(
         short = "u",
         long = "units",
         default_value = "human",
-        raw(possible_values = "&Units::arg_variants()", case_insensitive = "true")
+        raw(
+            possible_values = "&Units::arg_variants()",
+            case_insensitive = "true",
+            global = "true"
+        )
     )]
-    /// Measurement units displayed
+    /// Measurement units displayed, unless units are chosen for the specific quantity, e.g. with `--energy-units`
     units: Units,
+
+    #[structopt(
+        long = "energy-units",
+        raw(
+            possible_values = "&EnergyUnit::arg_variants()",
+            case_insensitive = "true",
+            global = "true"
+        )
+    )]
+    /// Energy units: watt-hours, joules or charge in milliampere-hours or ampere-hours,
+    /// which is calculated from energy with the battery voltage.
+    /// Watt-hours are used for "human" units and joules for "si" ones by default
+    energy_units: Option<EnergyUnit>,
+
+    #[structopt(
+        long = "power-units",
+        default_value = "w",
+        raw(
+            possible_values = "&PowerUnit::arg_variants()",
+            case_insensitive = "true",
+            global = "true"
+        )
+    )]
+    /// Energy rate units: watts or milliwatts
+    power_units: PowerUnit,
+
+    #[structopt(
+        long = "current-units",
+        default_value = "a",
+        raw(
+            possible_values = "&CurrentUnit::arg_variants()",
+            case_insensitive = "true",
+            global = "true"
+        )
+    )]
+    /// Current units: amperes or milliamperes
+    current_units: CurrentUnit,
+
+    #[structopt(
+        long = "temperature-units",
+        raw(
+            possible_values = "&TemperatureUnit::arg_variants()",
+            case_insensitive = "true",
+            global = "true"
+        )
+    )]
+    /// Temperature units: degrees Celsius, kelvins or degrees Fahrenheit.
+    /// Degrees Celsius are used for "human" units and kelvins for "si" ones by default
+    temperature_units: Option<TemperatureUnit>,
+
+    #[structopt(
+        short = "c",
+        long = "chart",
+        raw(
+            possible_values = "&ChartType::arg_variants()",
+            case_insensitive = "true",
+            use_delimiter = "true",
+            number_of_values = "1"
+        )
+    )]
+    /// Charts displayed, might be repeated or comma-separated.
+    /// Voltage, energy rate and temperature charts are displayed by default
+    charts: Vec<ChartType>,
+
+    #[structopt(
+        short = "f",
+        long = "filter",
+        default_value = "none",
+        raw(possible_values = "&FilterType::arg_variants()", case_insensitive = "true")
+    )]
+    /// Smoothing filter applied to the energy rate
+    filter: FilterType,
+
+    #[structopt(
+        long = "filter-window",
+        default_value = "30s",
+        parse(try_from_str = "parse_human_duration")
+    )]
+    /// Period used by the moving average and median filters or the EMA filter time constant
+    filter_window: Duration,
+
+    #[structopt(
+        long = "estimate-window",
+        default_value = "10m",
+        parse(try_from_str = "parse_human_duration")
+    )]
+    /// Period of the energy history used to estimate time to empty or full
+    estimate_window: Duration,
+
+    #[structopt(long = "no-store", raw(global = "true"))]
+    /// Do not store samples in the `$XDG_DATA_HOME/battop/` directory and do not load them on start
+    no_store: bool,
+
+    #[structopt(
+        long = "health-threshold",
+        default_value = "80",
+        parse(try_from_str = "parse_percent"),
+        raw(global = "true")
+    )]
+    /// State of health percent at which battery should be replaced, used for the health trend projection
+    health_threshold: f64,
+
+    #[structopt(short = "b", long = "battery", raw(number_of_values = "1", global = "true"))]
+    /// Show only batteries matching the selector: index (starting from zero), system name (e.g. BAT0),
+    /// vendor, model or serial number. Might be repeated, battery matching the first one is selected on start
+    batteries: Vec<String>,
+
+    #[structopt(
+        long = "alias",
+        parse(try_from_str = "parse_alias"),
+        raw(number_of_values = "1", global = "true")
+    )]
+    /// Name displayed for the battery with the given serial number instead of its model,
+    /// in SERIAL=NAME form, e.g. "1234=Left pack". Might be repeated
+    aliases: Vec<(String, String)>,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(StructOpt, Debug)]
+pub enum Command {
+    #[structopt(name = "tui")]
+    /// Interactive batteries viewer, used when no command is given
+    Tui,
+
+    #[structopt(name = "list")]
+    /// List all batteries installed
+    List,
+
+    #[structopt(name = "get")]
+    /// Print one value of the battery, e.g. `battop get BAT0 soc`
+    Get {
+        /// Battery index (starting from zero), system name (e.g. BAT0), vendor, model or serial number
+        battery: String,
+
+        #[structopt(raw(possible_values = "&Field::arg_variants()", case_insensitive = "true"))]
+        /// Battery value to print
+        field: Field,
+    },
+
+    #[structopt(name = "watch")]
+    /// Print batteries values as tab-separated lines every `--delay` seconds
+    Watch {
+        #[structopt(raw(possible_values = "&Field::arg_variants()", case_insensitive = "true"))]
+        /// Battery values to print, state, state of charge, energy rate and voltage are printed by default
+        fields: Vec<Field>,
+    },
+
+    #[structopt(name = "export")]
+    /// Export stored samples as CSV
+    Export {
+        /// Battery to export samples for, all batteries are exported if omitted
+        battery: Option<String>,
+
+        #[structopt(long = "since", parse(try_from_str = "parse_human_duration"))]
+        /// Export only samples taken during this period, e.g. "1h" or "7days"
+        since: Option<Duration>,
+
+        #[structopt(short = "o", long = "output", parse(from_os_str))]
+        /// File to write samples into, stdout is used if omitted
+        output: Option<PathBuf>,
+    },
+
+    #[structopt(name = "report")]
+    /// Render static report for all batteries from their current state and the stored history
+    Report {
+        #[structopt(
+            long = "format",
+            default_value = "markdown",
+            raw(possible_values = "&ReportFormat::arg_variants()", case_insensitive = "true")
+        )]
+        /// Report format
+        format: ReportFormat,
+
+        #[structopt(short = "o", long = "output", parse(from_os_str))]
+        /// File to write report into, stdout is used if omitted
+        output: Option<PathBuf>,
+    },
 }
 
 impl Config {
@@ -51,11 +323,123 @@ impl Config {
         self.verbose
     }
 
+    pub fn log_file(&self) -> Option<&Path> {
+        self.log_file.as_ref().map(PathBuf::as_path)
+    }
+
     pub fn delay(&self) -> &Duration {
         &self.delay
     }
 
-    pub fn units(&self) -> Units {
-        self.units
+    pub fn redraw_interval(&self) -> &Duration {
+        &self.redraw_interval
+    }
+
+    pub fn history(&self) -> &Duration {
+        &self.history
+    }
+
+    pub fn energy_units(&self) -> EnergyUnit {
+        self.energy_units.unwrap_or_else(|| self.units.energy())
+    }
+
+    pub fn power_units(&self) -> PowerUnit {
+        self.power_units
+    }
+
+    pub fn current_units(&self) -> CurrentUnit {
+        self.current_units
+    }
+
+    pub fn temperature_units(&self) -> TemperatureUnit {
+        self.temperature_units.unwrap_or_else(|| self.units.temperature())
+    }
+
+    pub fn filter(&self) -> FilterType {
+        self.filter
+    }
+
+    pub fn filter_window(&self) -> &Duration {
+        &self.filter_window
+    }
+
+    pub fn estimate_window(&self) -> &Duration {
+        &self.estimate_window
+    }
+
+    /// Should samples be stored on disk
+    pub fn store(&self) -> bool {
+        !self.no_store
+    }
+
+    pub fn health_threshold(&self) -> f64 {
+        self.health_threshold
+    }
+
+    /// Battery selectors, all batteries should be used if empty
+    pub fn batteries(&self) -> &[String] {
+        self.batteries.as_ref()
+    }
+
+    /// Name chosen by user for the battery with `serial` number
+    pub fn alias(&self, serial: &str) -> Option<&str> {
+        self.aliases
+            .iter()
+            .find(|(alias_serial, _)| alias_serial == serial)
+            .map(|(_, name)| name.as_str())
+    }
+
+    pub fn command(&self) -> &Command {
+        self.command.as_ref().unwrap_or(&Command::Tui)
+    }
+
+    pub fn charts(&self) -> &[ChartType] {
+        if self.charts.is_empty() {
+            DEFAULT_CHARTS
+        } else {
+            self.charts.as_ref()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use structopt::StructOpt;
+
+    use super::{Command, Config};
+    use crate::app::ui::PowerUnit;
+
+    #[test]
+    fn global_options_after_command() {
+        let config = Config::from_iter(&["battop", "watch", "-d", "5", "--power-units", "mw", "-b", "BAT1"]);
+
+        match config.command() {
+            Command::Watch {
+                ..
+            } => {}
+            command => panic!("Unexpected command {:?}", command),
+        }
+        assert_eq!(*config.delay(), Duration::from_secs(5));
+        assert_eq!(config.power_units(), PowerUnit::Milliwatt);
+        assert_eq!(config.batteries(), &["BAT1".to_string()]);
+    }
+
+    #[test]
+    fn global_options_before_command() {
+        let config = Config::from_iter(&["battop", "-d", "5", "-b", "BAT1", "report"]);
+
+        assert_eq!(*config.delay(), Duration::from_secs(5));
+        assert_eq!(config.batteries(), &["BAT1".to_string()]);
+    }
+
+    #[test]
+    fn default_options() {
+        let config = Config::from_iter(&["battop", "list"]);
+
+        assert_eq!(*config.delay(), Duration::from_secs(1));
+        assert_eq!(config.power_units(), PowerUnit::Watt);
+        assert!(config.batteries().is_empty());
     }
 }
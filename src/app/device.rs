@@ -0,0 +1,323 @@
+use crate::{Error, Result, ResultExt};
+
+/// Battery found in system along with the information needed to address it
+#[derive(Debug)]
+pub struct Device {
+    // Position in the batteries list, starting from zero
+    index: usize,
+    // System name, e.g. `BAT0` on Linux
+    name: Option<String>,
+    battery: battery::Battery,
+}
+
+impl Device {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(String::as_str)
+    }
+
+    /// Short battery identifier, system name if available or index otherwise
+    pub fn id(&self) -> String {
+        match self.name {
+            Some(ref name) => name.clone(),
+            None => self.index.to_string(),
+        }
+    }
+
+    pub fn battery(&self) -> &battery::Battery {
+        &self.battery
+    }
+
+    pub fn battery_mut(&mut self) -> &mut battery::Battery {
+        &mut self.battery
+    }
+
+    pub fn into_battery(self) -> battery::Battery {
+        self.battery
+    }
+
+    /// Check if device is matching user-provided `selector`,
+    /// which might be an index, system name, vendor, model or serial number
+    pub fn matches(&self, selector: &str) -> bool {
+        matches(&self.keys(), selector)
+    }
+
+    fn keys(&self) -> Keys<'_> {
+        (
+            self.index,
+            [
+                self.name(),
+                self.battery.vendor(),
+                self.battery.model(),
+                self.battery.serial_number(),
+            ],
+        )
+    }
+}
+
+/// Device index along with its system name, vendor, model and serial number, which are used by selectors
+type Keys<'a> = (usize, [Option<&'a str>; 4]);
+
+fn matches(keys: &Keys, selector: &str) -> bool {
+    let (index, values) = keys;
+    let selector = selector.trim();
+    if selector.parse::<usize>().ok() == Some(*index) {
+        return true;
+    }
+
+    values
+        .iter()
+        .flatten()
+        .any(|value| value.trim().eq_ignore_ascii_case(selector))
+}
+
+/// Position of the first device matching `selector`.
+///
+/// Numeric selector might be both an index and a serial number, index is preferred then.
+fn position(keys: &[Keys], selector: &str) -> Option<usize> {
+    let index = selector.trim().parse::<usize>().ok();
+
+    keys.iter()
+        .position(|(device_index, _)| Some(*device_index) == index)
+        .or_else(|| keys.iter().position(|keys| matches(keys, selector)))
+}
+
+/// Create batteries manager, which is used to access all batteries information
+pub fn manager() -> Result<battery::Manager> {
+    battery::Manager::new().context(|| "Unable to access batteries information".to_string())
+}
+
+/// Find all batteries installed.
+///
+/// `Error::NoBatteries` is returned if there are none.
+pub fn devices(manager: &battery::Manager) -> Result<Vec<Device>> {
+    let batteries = manager
+        .batteries()
+        .context(|| "Unable to list batteries".to_string())?
+        .filter_map(|battery| match battery {
+            Ok(battery) => Some(battery),
+            Err(e) => {
+                warn!("Unable to access battery: {}", e);
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    let names = system_names(&batteries);
+    let devices = batteries
+        .into_iter()
+        .zip(names)
+        .enumerate()
+        .map(|(index, (battery, name))| Device {
+            index,
+            name,
+            battery,
+        })
+        .collect::<Vec<_>>();
+
+    if devices.is_empty() {
+        debug!("Unable to find any batteries in system");
+        Err(Error::NoBatteries)
+    } else {
+        trace!("Found {} batteries", devices.len());
+        Ok(devices)
+    }
+}
+
+/// Find batteries matching any of the `selectors`, see `Device::matches`.
+///
+/// All batteries are returned if there are no selectors.
+pub fn selected(manager: &battery::Manager, selectors: &[String]) -> Result<Vec<Device>> {
+    let devices = devices(manager)?;
+    if selectors.is_empty() {
+        return Ok(devices);
+    }
+
+    let devices = devices
+        .into_iter()
+        .filter(|device| selectors.iter().any(|selector| device.matches(selector)))
+        .collect::<Vec<_>>();
+    if devices.is_empty() {
+        debug!("Unable to find any batteries matching {:?}", selectors);
+        Err(Error::BatteryNotFound(selectors.join(", ")))
+    } else {
+        Ok(devices)
+    }
+}
+
+/// Find the device matching `selector`, see `Device::matches` and `position`
+pub fn find(manager: &battery::Manager, selector: &str) -> Result<Device> {
+    let mut devices = devices(manager)?;
+    let keys = devices.iter().map(Device::keys).collect::<Vec<_>>();
+
+    match position(&keys, selector) {
+        Some(position) => Ok(devices.swap_remove(position)),
+        None => Err(Error::BatteryNotFound(selector.to_string())),
+    }
+}
+
+/// Position of the device selected on start, which is the one matching the first of `selectors`
+pub fn initial(devices: &[Device], selectors: &[String]) -> usize {
+    let keys = devices.iter().map(Device::keys).collect::<Vec<_>>();
+
+    initial_position(&keys, selectors)
+}
+
+fn initial_position(keys: &[Keys], selectors: &[String]) -> usize {
+    selectors
+        .first()
+        .and_then(|selector| position(keys, selector))
+        .unwrap_or(0)
+}
+
+/// Vendor, model and serial number, which are identifying the battery
+type Identity = [Option<String>; 3];
+
+fn identity(vendor: Option<&str>, model: Option<&str>, serial_number: Option<&str>) -> Identity {
+    let normalize = |value: Option<&str>| value.map(str::trim).filter(|value| !value.is_empty()).map(String::from);
+
+    [normalize(vendor), normalize(model), normalize(serial_number)]
+}
+
+/// System names of the `batteries`, e.g. `BAT0`, in the same order.
+///
+/// Power supplies are matched with batteries by their identity, not by position,
+/// as some of them might fail to be listed by either side.
+/// Identical batteries are matched in the listing order, but only if there are as many power supplies
+/// with that identity as batteries; name is unknown otherwise.
+fn system_names(batteries: &[battery::Battery]) -> Vec<Option<String>> {
+    let supplies = system_batteries();
+    let identities = batteries
+        .iter()
+        .map(|battery| identity(battery.vendor(), battery.model(), battery.serial_number()))
+        .collect::<Vec<_>>();
+
+    identities
+        .iter()
+        .enumerate()
+        .map(|(index, identity)| {
+            let candidates = supplies
+                .iter()
+                .filter(|(_, supply)| supply == identity)
+                .collect::<Vec<_>>();
+            let twins = identities.iter().filter(|other| *other == identity).count();
+            if candidates.len() != twins {
+                debug!("Unable to determine system name for battery #{}", index);
+                return None;
+            }
+            let position = identities[..index].iter().filter(|other| *other == identity).count();
+
+            candidates.get(position).map(|(name, _)| name.clone())
+        })
+        .collect()
+}
+
+/// Power supplies, which are considered as system batteries by `battery` crate, with their identities
+#[cfg(target_os = "linux")]
+fn system_batteries() -> Vec<(String, Identity)> {
+    use std::fs;
+    use std::path::Path;
+
+    let read = |path: &Path, name| fs::read_to_string(path.join(name)).map(|value| value.trim().to_string());
+    let is_battery = |path: &Path| {
+        let is_battery = match read(path, "type") {
+            Ok(value) => value.eq_ignore_ascii_case("battery"),
+            Err(_) => false,
+        };
+        // Power supply without `scope` is a system one
+        let is_system = match read(path, "scope") {
+            Ok(value) => value.eq_ignore_ascii_case("system"),
+            Err(_) => true,
+        };
+
+        is_battery && is_system
+    };
+
+    match fs::read_dir("/sys/class/power_supply/") {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| is_battery(path))
+            .filter_map(|path| {
+                let name = path.file_name()?.to_string_lossy().into_owned();
+                let value = |name| read(&path, name).ok();
+                let identity = identity(
+                    value("manufacturer").as_ref().map(String::as_str),
+                    value("model_name").as_ref().map(String::as_str),
+                    value("serial_number").as_ref().map(String::as_str),
+                );
+
+                Some((name, identity))
+            })
+            .collect(),
+        Err(e) => {
+            debug!("Unable to list power supplies: {}", e);
+            vec![]
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn system_batteries() -> Vec<(String, Identity)> {
+    vec![]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{initial_position, matches, position, Keys};
+
+    fn devices() -> Vec<Keys<'static>> {
+        vec![
+            (0, [Some("BAT0"), Some("LGC"), Some("45N1011"), Some("1")]),
+            (1, [Some("BAT1"), Some(" SANYO "), Some("45N1011"), Some("ab12 ")]),
+            (2, [None, None, None, None]),
+        ]
+    }
+
+    #[test]
+    fn matches_values() {
+        let devices = devices();
+
+        assert!(matches(&devices[0], "bat0"));
+        assert!(matches(&devices[0], " lgc"));
+        assert!(matches(&devices[1], "sanyo"));
+        assert!(matches(&devices[1], "AB12"));
+        assert!(matches(&devices[2], "2"));
+        assert!(!matches(&devices[2], "BAT2"));
+        assert!(!matches(&devices[0], "45N"));
+    }
+
+    #[test]
+    fn index_over_serial_number() {
+        let devices = devices();
+
+        // First battery serial number is "1", but index is preferred
+        assert!(matches(&devices[0], "1"));
+        assert_eq!(position(&devices, "1"), Some(1));
+        assert_eq!(position(&devices, " 0 "), Some(0));
+    }
+
+    #[test]
+    fn first_matching_position() {
+        let devices = devices();
+
+        assert_eq!(position(&devices, "45n1011"), Some(0));
+        assert_eq!(position(&devices, "BAT1"), Some(1));
+        assert_eq!(position(&devices, "BAT3"), None);
+        // Serial number is used if there is no battery with such index
+        assert_eq!(position(&[devices[2], devices[0]], "1"), Some(1));
+    }
+
+    #[test]
+    fn initial_by_first_selector() {
+        let devices = devices();
+        let selectors = |selectors: &[&str]| selectors.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(initial_position(&devices, &selectors(&["BAT1", "BAT0"])), 1);
+        assert_eq!(initial_position(&devices, &selectors(&["LGC", "1"])), 0);
+        assert_eq!(initial_position(&devices, &selectors(&["unknown", "BAT1"])), 0);
+        assert_eq!(initial_position(&devices, &[]), 0);
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use battery::State;
+
+use crate::app::time;
+
+/// Battery is charged with constant current until this share of the full energy,
+/// charging rate is decreasing after that, while charger is keeping the constant voltage.
+const CONSTANT_CURRENT_SHARE: f64 = 0.8;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Confidence::Low => f.write_str("low"),
+            Confidence::Medium => f.write_str("medium"),
+            Confidence::High => f.write_str("high"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Estimate {
+    pub state: State,
+    pub time: Duration,
+    pub confidence: Confidence,
+}
+
+/// Time to empty or full estimator, independent from the values reported by firmware.
+///
+/// Energy samples for the `window` period are fitted with a linear regression,
+/// which slope is the average energy rate. Charging estimation takes into account
+/// that the charging rate is slowing down when battery is almost full (CC/CV charging).
+#[derive(Debug)]
+pub struct Estimator {
+    window: Duration,
+    state: State,
+    // Energy samples in watt-hours
+    samples: VecDeque<(SystemTime, f64)>,
+}
+
+impl Estimator {
+    pub fn new(window: Duration) -> Estimator {
+        Estimator {
+            window,
+            state: State::Unknown,
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, timestamp: SystemTime, state: State, energy: f64) {
+        // Samples for a different state are useless, battery was charging and now it's not
+        if state != self.state {
+            self.samples.clear();
+            self.state = state;
+        }
+
+        self.samples.push_back((timestamp, energy));
+        while let Some((oldest, _)) = self.samples.front() {
+            match timestamp.duration_since(*oldest) {
+                Ok(age) if age > self.window => {
+                    self.samples.pop_front();
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Estimate time to empty or full for the battery with `energy_full` watt-hours capacity.
+    ///
+    /// `None` is returned if battery is not charging or discharging or there is not enough data yet.
+    pub fn estimate(&self, energy_full: f64) -> Option<Estimate> {
+        let (energy, span) = match (self.samples.front(), self.samples.back()) {
+            (Some((first, _)), Some((last, energy))) => (*energy, last.duration_since(*first).ok()?),
+            _ => return None,
+        };
+        let (slope, r_squared) = self.regression()?;
+
+        let seconds = match self.state {
+            State::Discharging if slope < 0.0 => energy / -slope,
+            State::Charging if slope > 0.0 => {
+                let threshold = energy_full * CONSTANT_CURRENT_SHARE;
+                if energy < threshold {
+                    // Constant current phase with the current rate and then rate is linearly decreasing
+                    // down to zero, which takes twice as long as it would with a constant rate
+                    (threshold - energy) / slope + 2.0 * (energy_full - threshold) / slope
+                } else {
+                    2.0 * (energy_full - energy).max(0.0) / slope
+                }
+            }
+            _ => return None,
+        };
+        if !seconds.is_finite() {
+            return None;
+        }
+
+        let confidence = match () {
+            _ if r_squared >= 0.9 && span >= Duration::from_secs(300) => Confidence::High,
+            _ if r_squared >= 0.6 && span >= Duration::from_secs(60) => Confidence::Medium,
+            _ => Confidence::Low,
+        };
+
+        Some(Estimate {
+            state: self.state,
+            time: Duration::from_secs(seconds as u64),
+            confidence,
+        })
+    }
+
+    /// Least squares fit for the samples, returns slope in watt-hours per second
+    /// and the coefficient of determination
+    fn regression(&self) -> Option<(f64, f64)> {
+        if self.samples.len() < 3 {
+            return None;
+        }
+        let (start, _) = self.samples.front()?;
+        let points = self
+            .samples
+            .iter()
+            .map(|(timestamp, energy)| {
+                let x = time::seconds(timestamp.duration_since(*start).unwrap_or_default());
+                (x, *energy)
+            })
+            .collect::<Vec<_>>();
+
+        let count = points.len() as f64;
+        let x_mean = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+        let y_mean = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+        let (mut xy, mut xx, mut yy) = (0.0, 0.0, 0.0);
+        for (x, y) in &points {
+            xy += (x - x_mean) * (y - y_mean);
+            xx += (x - x_mean).powi(2);
+            yy += (y - y_mean).powi(2);
+        }
+        if xx == 0.0 {
+            return None;
+        }
+
+        let slope = xy / xx;
+        // Perfectly flat energy is a perfect fit too, but slope will be rejected later anyway
+        let r_squared = if yy == 0.0 { 1.0 } else { xy * xy / (xx * yy) };
+
+        Some((slope, r_squared))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use battery::State;
+
+    use super::{Confidence, Estimator};
+    use crate::app::sample::fixtures::at;
+
+    /// Estimator fed with samples taken every `step` seconds during `span` seconds,
+    /// energy is changing linearly from `start` by `rate` watts
+    fn estimator(state: State, start: f64, rate: f64, step: u64, span: u64) -> Estimator {
+        let mut estimator = Estimator::new(Duration::from_secs(3600));
+        for seconds in (0..=span).step_by(step as usize) {
+            estimator.push(at(seconds), state, start + rate * seconds as f64 / 3600.0);
+        }
+
+        estimator
+    }
+
+    fn assert_seconds(actual: Duration, expected: u64) {
+        let actual = actual.as_secs();
+        assert!(
+            actual + 1 >= expected && actual <= expected + 1,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn not_enough_samples() {
+        let estimator = estimator(State::Discharging, 50.0, -10.0, 60, 60);
+
+        assert!(estimator.estimate(100.0).is_none());
+    }
+
+    #[test]
+    fn discharging() {
+        // 10 W discharge from 50 Wh during 10 minutes, leaving 48.33 Wh for 4.83 hours
+        let estimate = estimator(State::Discharging, 50.0, -10.0, 60, 600)
+            .estimate(100.0)
+            .unwrap();
+
+        assert_eq!(estimate.state, State::Discharging);
+        assert_seconds(estimate.time, 17_400);
+        assert_eq!(estimate.confidence, Confidence::High);
+    }
+
+    #[test]
+    fn discharging_with_growing_energy() {
+        let estimator = estimator(State::Discharging, 50.0, 10.0, 60, 600);
+
+        assert!(estimator.estimate(100.0).is_none());
+    }
+
+    #[test]
+    fn charging_constant_current() {
+        // 100 W charge reaches 43.33 Wh after 2 minutes, so it takes 1320 s up to 80 Wh
+        // and then twice as long as with a constant rate to charge the remaining 20 Wh, which is 1440 s
+        let estimate = estimator(State::Charging, 40.0, 100.0, 10, 120)
+            .estimate(100.0)
+            .unwrap();
+
+        assert_eq!(estimate.state, State::Charging);
+        assert_seconds(estimate.time, 2760);
+        assert_eq!(estimate.confidence, Confidence::Medium);
+    }
+
+    #[test]
+    fn charging_constant_voltage_tail() {
+        // 93.33 Wh after 2 minutes, with the remaining 6.67 Wh charged with a linearly decreasing rate
+        let estimate = estimator(State::Charging, 90.0, 100.0, 10, 120)
+            .estimate(100.0)
+            .unwrap();
+
+        assert_seconds(estimate.time, 480);
+    }
+
+    #[test]
+    fn state_change_resets_samples() {
+        let mut estimator = estimator(State::Discharging, 50.0, -10.0, 60, 600);
+        estimator.push(at(660), State::Charging, 48.0);
+
+        assert!(estimator.estimate(100.0).is_none());
+    }
+}
@@ -1,89 +1,226 @@
 use std::io;
-use std::sync::mpsc;
+use std::os::raw::c_int;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
 use std::thread;
+use std::time::Duration;
 
-use termion::event::Key;
+use signal_hook::iterator::Signals;
+use signal_hook::{SIGHUP, SIGTERM, SIGUSR1, SIGWINCH};
+use termion::event::{Event as TermEvent, Key, MouseButton, MouseEvent};
 use termion::input::TermRead;
 
 use crate::app::Config;
-use crate::Result;
+use crate::{Result, ResultExt};
 
-#[derive(Debug, Eq, PartialEq)]
+/// Signals translated into events: exit, snapshot and resize
+const HANDLED_SIGNALS: &[c_int] = &[SIGTERM, SIGHUP, SIGUSR1, SIGWINCH];
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum Event {
     Exit,
     NextTab,
     PreviousTab,
+    Pause,
+    CursorBack,
+    CursorForward,
+    ZoomIn,
+    ZoomOut,
+    MeanLine,
+    Sessions,
+    Health,
+    Log,
+    Help,
+    // Log pane scrolling
+    LineUp,
+    LineDown,
+    // Mouse events with the zero-based terminal cell coordinates
+    Click(u16, u16),
+    Drag(u16, u16),
+    ScrollUp(u16, u16),
+    ScrollDown(u16, u16),
+    Refresh,
+    // Requested with `SIGUSR1`
+    Snapshot,
+    // Terminal was resized, as reported by `SIGWINCH`
+    Resize,
     Tick,
+    Redraw,
 }
 
 #[derive(Debug)]
 pub struct EventHandler {
     rx: mpsc::Receiver<Event>,
-    input_handle: thread::JoinHandle<()>,
-    tick_handle: thread::JoinHandle<()>,
+    // Set when handler is dropped, so input thread could terminate after the next input
+    shutdown: Arc<AtomicBool>,
+    input_handle: Option<thread::JoinHandle<()>>,
+    // Set by the input thread right before it terminates
+    input_done: Arc<AtomicBool>,
+    // Signals thread is stopped when signals iterator is closed
+    signals: Signals,
+    signals_handle: Option<thread::JoinHandle<()>>,
+    // Timer threads are stopped when their channels are closed
+    timers: Vec<(mpsc::Sender<()>, thread::JoinHandle<()>)>,
 }
 
 impl EventHandler {
-    pub fn from_config(config: &Config) -> EventHandler {
+    pub fn from_config(config: &Config) -> Result<EventHandler> {
         let (tx, rx) = mpsc::channel();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let input_done = Arc::new(AtomicBool::new(false));
 
         // Thread than will handle user input and send events to receiver
         let input_handle = {
             let tx = tx.clone();
+            let shutdown = shutdown.clone();
+            let done = input_done.clone();
             thread::spawn(move || {
-                let stdin = io::stdin();
-                trace!("Input thread spawned");
-                for possible_key in stdin.keys() {
-                    if let Ok(key) = possible_key {
-                        let event = match key {
-                            Key::Left => Event::PreviousTab,
-                            Key::Right => Event::NextTab,
-                            Key::Char('q') => Event::Exit,
-                            Key::Ctrl('c') => Event::Exit,
-                            Key::Esc => Event::Exit,
-                            _ => continue,
-                        };
-                        let is_exit = event == Event::Exit;
-
-                        if let Err(e) = tx.send(event) {
-                            // Now that's just terrible thing to do with poor thread :(
-                            warn!("Input thread failed to send event and will be terminated: {:?}", e);
-                            return;
-                        }
-
-                        // User had requested an exit, closing this thread too
-                        if is_exit {
-                            trace!("Input thread just sent the Exit event and going to terminate now");
-                            return;
-                        }
-                    }
-                }
+                EventHandler::read_input(&tx, &shutdown);
+                done.store(true, Ordering::SeqCst);
             })
         };
 
-        // Thread that will "tick" with some user-defined interval.
-        // Application might update state and re-draw UI on that event
-        let interval = *config.delay();
-        let tick_handle = {
+        // Thread that will translate Unix signals into events, so they are handled
+        // in the same way as user input, e.g. terminal is restored on `SIGTERM`
+        let signals = Signals::new(HANDLED_SIGNALS).context(|| "Unable to register signal handlers".to_string())?;
+        let signals_handle = {
+            let tx = tx.clone();
+            let signals = signals.clone();
             thread::spawn(move || {
-                let tx = tx.clone();
-                trace!("Tick thread is spawned with {:?} interval", interval);
-                loop {
-                    tx.send(Event::Tick).expect("Tick receiver is dead");
-                    thread::sleep(interval);
+                trace!("Signals thread spawned");
+                for signal in signals.forever() {
+                    let event = match signal {
+                        SIGTERM | SIGHUP => Event::Exit,
+                        SIGUSR1 => Event::Snapshot,
+                        SIGWINCH => Event::Resize,
+                        _ => continue,
+                    };
+                    debug!("Received signal {}, sending {:?} event", signal, event);
+                    if tx.send(event).is_err() {
+                        debug!("Events receiver is gone, terminating signals thread");
+                        return;
+                    }
                 }
+                trace!("Signals thread is stopped");
             })
         };
 
-        EventHandler {
+        let timers = vec![
+            // Thread that will "tick" with some user-defined interval.
+            // Application might update state and re-draw UI on that event
+            EventHandler::spawn_timer("Tick", tx.clone(), Event::Tick, *config.delay()),
+            // Thread that will check if UI should be re-drawn, independently from the updates interval,
+            // e.g. when terminal was resized
+            EventHandler::spawn_timer("Redraw", tx, Event::Redraw, *config.redraw_interval()),
+        ];
+
+        Ok(EventHandler {
             rx,
-            input_handle,
-            tick_handle,
+            shutdown,
+            input_handle: Some(input_handle),
+            input_done,
+            signals,
+            signals_handle: Some(signals_handle),
+            timers,
+        })
+    }
+
+    /// Read user input and send events to `tx` until the exit key, an error or the `shutdown` flag
+    fn read_input(tx: &mpsc::Sender<Event>, shutdown: &AtomicBool) {
+        let stdin = io::stdin();
+        trace!("Input thread spawned");
+        for possible_event in stdin.events() {
+            if shutdown.load(Ordering::SeqCst) {
+                trace!("Input thread is shut down");
+                return;
+            }
+            let input = match possible_event {
+                Ok(input) => input,
+                Err(e) => {
+                    warn!("Input thread failed to read from stdin and will be terminated: {}", e);
+                    return;
+                }
+            };
+            let event = match input {
+                TermEvent::Key(Key::Left) => Event::PreviousTab,
+                TermEvent::Key(Key::Right) => Event::NextTab,
+                TermEvent::Key(Key::Char('p')) | TermEvent::Key(Key::Char(' ')) => Event::Pause,
+                TermEvent::Key(Key::Char(',')) => Event::CursorBack,
+                TermEvent::Key(Key::Char('.')) => Event::CursorForward,
+                TermEvent::Key(Key::Char('+')) | TermEvent::Key(Key::Char('=')) => Event::ZoomIn,
+                TermEvent::Key(Key::Char('-')) => Event::ZoomOut,
+                TermEvent::Key(Key::Char('m')) => Event::MeanLine,
+                TermEvent::Key(Key::Char('s')) => Event::Sessions,
+                TermEvent::Key(Key::Char('h')) => Event::Health,
+                TermEvent::Key(Key::Char('l')) => Event::Log,
+                TermEvent::Key(Key::Up) => Event::LineUp,
+                TermEvent::Key(Key::Down) => Event::LineDown,
+                TermEvent::Key(Key::Char('?')) => Event::Help,
+                TermEvent::Key(Key::Char('r')) => Event::Refresh,
+                TermEvent::Key(Key::Char('q')) => Event::Exit,
+                TermEvent::Key(Key::Ctrl('c')) => Event::Exit,
+                TermEvent::Key(Key::Esc) => Event::Exit,
+                // termion coordinates are starting from one
+                TermEvent::Mouse(MouseEvent::Press(MouseButton::Left, x, y)) => {
+                    Event::Click(x.saturating_sub(1), y.saturating_sub(1))
+                }
+                TermEvent::Mouse(MouseEvent::Press(MouseButton::WheelUp, x, y)) => {
+                    Event::ScrollUp(x.saturating_sub(1), y.saturating_sub(1))
+                }
+                TermEvent::Mouse(MouseEvent::Press(MouseButton::WheelDown, x, y)) => {
+                    Event::ScrollDown(x.saturating_sub(1), y.saturating_sub(1))
+                }
+                TermEvent::Mouse(MouseEvent::Hold(x, y)) => Event::Drag(x.saturating_sub(1), y.saturating_sub(1)),
+                _ => continue,
+            };
+            let is_exit = event == Event::Exit;
+
+            if let Err(e) = tx.send(event) {
+                // Now that's just terrible thing to do with poor thread :(
+                warn!("Input thread failed to send event and will be terminated: {:?}", e);
+                return;
+            }
+
+            // User had requested an exit, closing this thread too
+            if is_exit {
+                trace!("Input thread just sent the Exit event and going to terminate now");
+                return;
+            }
         }
     }
 
+    /// Spawn thread sending `event` every `interval` until the returned sender is dropped
+    fn spawn_timer(
+        name: &'static str,
+        tx: mpsc::Sender<Event>,
+        event: Event,
+        interval: Duration,
+    ) -> (mpsc::Sender<()>, thread::JoinHandle<()>) {
+        let (stop_tx, stop_rx) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            trace!("{} thread is spawned with {:?} interval", name, interval);
+            loop {
+                if tx.send(event).is_err() {
+                    debug!("{} receiver is gone, terminating {} thread", name, name.to_lowercase());
+                    return;
+                }
+                match stop_rx.recv_timeout(interval) {
+                    Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                    _ => {
+                        trace!("{} thread is stopped", name);
+                        return;
+                    }
+                }
+            }
+        });
+
+        (stop_tx, handle)
+    }
+
     pub fn next(&self) -> Result<Event> {
         match self.rx.recv() {
+            // Redraw checks are way too often to log them
+            Ok(Event::Redraw) => Ok(Event::Redraw),
             Ok(event) => {
                 trace!("UI thread had received an event: {:?}", event);
                 Ok(event)
@@ -92,3 +229,32 @@ impl EventHandler {
         }
     }
 }
+
+impl Drop for EventHandler {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        for (stop, handle) in self.timers.drain(..) {
+            drop(stop);
+            if handle.join().is_err() {
+                error!("Timer thread had panicked");
+            }
+        }
+
+        self.signals.close();
+        if let Some(handle) = self.signals_handle.take() {
+            if handle.join().is_err() {
+                error!("Signals thread had panicked");
+            }
+        }
+
+        // Reading from stdin can't be interrupted, so input thread is joined only if it is done already,
+        // e.g. after the exit key; otherwise it will terminate after the next input or along with process
+        if let Some(handle) = self.input_handle.take() {
+            if !self.input_done.load(Ordering::SeqCst) {
+                debug!("Input thread is blocked on stdin, leaving it to terminate along with process");
+            } else if handle.join().is_err() {
+                error!("Input thread had panicked");
+            }
+        }
+    }
+}
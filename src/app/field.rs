@@ -0,0 +1,184 @@
+use std::fmt;
+use std::str::FromStr;
+
+use battery::units::time::second;
+
+use super::sample::Sample;
+use super::Config;
+use crate::Error;
+
+/// Battery property which can be requested from the command line
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Field {
+    Vendor,
+    Model,
+    Serial,
+    Technology,
+    State,
+    StateOfCharge,
+    StateOfHealth,
+    Energy,
+    EnergyFull,
+    EnergyFullDesign,
+    EnergyRate,
+    Voltage,
+    Current,
+    Temperature,
+    CycleCount,
+    TimeToFull,
+    TimeToEmpty,
+}
+
+/// All fields, in the same order as they are listed in help
+pub const ALL_FIELDS: &[Field] = &[
+    Field::Vendor,
+    Field::Model,
+    Field::Serial,
+    Field::Technology,
+    Field::State,
+    Field::StateOfCharge,
+    Field::StateOfHealth,
+    Field::Energy,
+    Field::EnergyFull,
+    Field::EnergyFullDesign,
+    Field::EnergyRate,
+    Field::Voltage,
+    Field::Current,
+    Field::Temperature,
+    Field::CycleCount,
+    Field::TimeToFull,
+    Field::TimeToEmpty,
+];
+
+/// Fields stored for each sample, in the order they are exported
+pub const SAMPLE_FIELDS: &[Field] = &[
+    Field::State,
+    Field::StateOfCharge,
+    Field::StateOfHealth,
+    Field::Energy,
+    Field::EnergyFull,
+    Field::EnergyFullDesign,
+    Field::EnergyRate,
+    Field::Voltage,
+    Field::Current,
+    Field::Temperature,
+    Field::CycleCount,
+];
+
+impl Field {
+    pub fn arg_variants() -> [&'static str; 17] {
+        [
+            "vendor",
+            "model",
+            "serial",
+            "technology",
+            "state",
+            "soc",
+            "soh",
+            "energy",
+            "energy-full",
+            "energy-full-design",
+            "energy-rate",
+            "voltage",
+            "current",
+            "temperature",
+            "cycles",
+            "time-to-full",
+            "time-to-empty",
+        ]
+    }
+
+    /// Plain field value without the measurement unit, suitable for scripts.
+    ///
+    /// Energy, energy rate, current and temperature are in the units chosen in `config`,
+    /// voltage is always in volts and time is in seconds.
+    pub fn value(self, battery: &battery::Battery, sample: &Sample, config: &Config) -> Option<String> {
+        let energy = |value: f64| {
+            let units = config.energy_units();
+            units
+                .convert(value, sample.voltage)
+                .map(|value| format!("{:.*}", units.precision(), value))
+        };
+
+        let value = match self {
+            Field::Vendor => battery.vendor()?.trim().to_string(),
+            Field::Model => battery.model()?.trim().to_string(),
+            Field::Serial => battery.serial_number()?.trim().to_string(),
+            Field::Technology => battery.technology().to_string(),
+            Field::State => sample.state.to_string(),
+            Field::StateOfCharge => format!("{:.2}", sample.state_of_charge),
+            Field::StateOfHealth => format!("{:.2}", sample.state_of_health),
+            Field::Energy => energy(sample.energy)?,
+            Field::EnergyFull => energy(sample.energy_full)?,
+            Field::EnergyFullDesign => energy(sample.energy_full_design)?,
+            Field::EnergyRate => {
+                let units = config.power_units();
+                format!("{:.*}", units.precision(), units.convert(sample.energy_rate))
+            }
+            Field::Voltage => format!("{:.2}", sample.voltage),
+            Field::Current => {
+                let units = config.current_units();
+                format!("{:.*}", units.precision(), units.convert(sample.current()?))
+            }
+            Field::Temperature => format!("{:.2}", config.temperature_units().convert(sample.temperature?)),
+            Field::CycleCount => sample.cycle_count?.to_string(),
+            Field::TimeToFull => format!("{:.0}", battery.time_to_full()?.get::<second>()),
+            Field::TimeToEmpty => format!("{:.0}", battery.time_to_empty()?.get::<second>()),
+        };
+
+        Some(value)
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Field::Vendor => "vendor",
+            Field::Model => "model",
+            Field::Serial => "serial",
+            Field::Technology => "technology",
+            Field::State => "state",
+            Field::StateOfCharge => "soc",
+            Field::StateOfHealth => "soh",
+            Field::Energy => "energy",
+            Field::EnergyFull => "energy-full",
+            Field::EnergyFullDesign => "energy-full-design",
+            Field::EnergyRate => "energy-rate",
+            Field::Voltage => "voltage",
+            Field::Current => "current",
+            Field::Temperature => "temperature",
+            Field::CycleCount => "cycles",
+            Field::TimeToFull => "time-to-full",
+            Field::TimeToEmpty => "time-to-empty",
+        };
+
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Field {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match () {
+            _ if s.eq_ignore_ascii_case("vendor") => Ok(Field::Vendor),
+            _ if s.eq_ignore_ascii_case("model") => Ok(Field::Model),
+            _ if s.eq_ignore_ascii_case("serial") => Ok(Field::Serial),
+            _ if s.eq_ignore_ascii_case("technology") => Ok(Field::Technology),
+            _ if s.eq_ignore_ascii_case("state") => Ok(Field::State),
+            _ if s.eq_ignore_ascii_case("soc") => Ok(Field::StateOfCharge),
+            _ if s.eq_ignore_ascii_case("soh") => Ok(Field::StateOfHealth),
+            _ if s.eq_ignore_ascii_case("energy") => Ok(Field::Energy),
+            _ if s.eq_ignore_ascii_case("energy-full") => Ok(Field::EnergyFull),
+            _ if s.eq_ignore_ascii_case("energy-full-design") => Ok(Field::EnergyFullDesign),
+            _ if s.eq_ignore_ascii_case("energy-rate") => Ok(Field::EnergyRate),
+            _ if s.eq_ignore_ascii_case("voltage") => Ok(Field::Voltage),
+            _ if s.eq_ignore_ascii_case("current") => Ok(Field::Current),
+            _ if s.eq_ignore_ascii_case("temperature") => Ok(Field::Temperature),
+            _ if s.eq_ignore_ascii_case("cycles") => Ok(Field::CycleCount),
+            _ if s.eq_ignore_ascii_case("time-to-full") => Ok(Field::TimeToFull),
+            _ if s.eq_ignore_ascii_case("time-to-empty") => Ok(Field::TimeToEmpty),
+            _ => Err(Error::Parse("field", s.to_string())),
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use crate::app::time;
+use crate::app::Config;
+use crate::Error;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum FilterType {
+    None,
+    MovingAverage,
+    Ema,
+    Median,
+}
+
+impl FilterType {
+    pub fn arg_variants() -> [&'static str; 4] {
+        ["none", "moving-average", "ema", "median"]
+    }
+}
+
+impl FromStr for FilterType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match () {
+            _ if s.eq_ignore_ascii_case("none") => Ok(FilterType::None),
+            _ if s.eq_ignore_ascii_case("moving-average") => Ok(FilterType::MovingAverage),
+            _ if s.eq_ignore_ascii_case("ema") => Ok(FilterType::Ema),
+            _ if s.eq_ignore_ascii_case("median") => Ok(FilterType::Median),
+            _ => Err(Error::Parse("filter", s.to_string())),
+        }
+    }
+}
+
+/// Smoothing filter for the noisy values, e.g. energy rate reported by firmware.
+///
+/// Moving average and median are calculated over the samples taken during the `window` period,
+/// while for the exponential moving average (EMA) `window` is a time constant.
+#[derive(Debug)]
+pub struct Filter {
+    filter_type: FilterType,
+    window: Duration,
+    samples: VecDeque<(SystemTime, f64)>,
+    // Latest EMA value and the moment it was calculated at
+    ema: Option<(SystemTime, f64)>,
+}
+
+impl Filter {
+    /// Create filter requested by user, if any
+    pub fn from_config(config: &Config) -> Option<Filter> {
+        match config.filter() {
+            FilterType::None => None,
+            filter_type => Some(Filter::new(filter_type, *config.filter_window())),
+        }
+    }
+
+    pub fn new(filter_type: FilterType, window: Duration) -> Filter {
+        Filter {
+            filter_type,
+            window,
+            samples: VecDeque::new(),
+            ema: None,
+        }
+    }
+
+    /// Feed the `value` taken at `timestamp` moment into filter and return the filtered value
+    pub fn apply(&mut self, timestamp: SystemTime, value: f64) -> f64 {
+        match self.filter_type {
+            FilterType::None => value,
+            FilterType::Ema => self.ema(timestamp, value),
+            FilterType::MovingAverage => {
+                self.push(timestamp, value);
+                let sum: f64 = self.samples.iter().map(|(_, value)| value).sum();
+                sum / self.samples.len() as f64
+            }
+            FilterType::Median => {
+                self.push(timestamp, value);
+                let mut values = self.samples.iter().map(|(_, value)| *value).collect::<Vec<_>>();
+                values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+                let middle = values.len() / 2;
+                if values.len() % 2 == 0 {
+                    (values[middle - 1] + values[middle]) / 2.0
+                } else {
+                    values[middle]
+                }
+            }
+        }
+    }
+
+    fn ema(&mut self, timestamp: SystemTime, value: f64) -> f64 {
+        let filtered = match self.ema {
+            Some((previous_timestamp, previous)) => {
+                let elapsed = time::seconds(timestamp.duration_since(previous_timestamp).unwrap_or_default());
+                // Smoothing factor depends on the time passed, so the irregular samples are weighted properly
+                let alpha = 1.0 - (-elapsed / time::seconds(self.window)).exp();
+                previous + alpha * (value - previous)
+            }
+            None => value,
+        };
+        self.ema = Some((timestamp, filtered));
+
+        filtered
+    }
+
+    fn push(&mut self, timestamp: SystemTime, value: f64) {
+        self.samples.push_back((timestamp, value));
+        while let Some((oldest, _)) = self.samples.front() {
+            match timestamp.duration_since(*oldest) {
+                Ok(age) if age > self.window => {
+                    self.samples.pop_front();
+                }
+                _ => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Filter, FilterType};
+    use crate::app::sample::fixtures::at;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn ema_first_value() {
+        let mut filter = Filter::new(FilterType::Ema, Duration::from_secs(10));
+
+        assert_close(filter.apply(at(0), 5.0), 5.0);
+    }
+
+    #[test]
+    fn ema_time_constant() {
+        let mut filter = Filter::new(FilterType::Ema, Duration::from_secs(10));
+        filter.apply(at(0), 0.0);
+
+        // Value is approaching the input by `1 - 1/e` share after one time constant
+        assert_close(filter.apply(at(10), 10.0), 10.0 * (1.0 - (-1.0f64).exp()));
+        // No time passed, so the value is not changed at all
+        assert_close(filter.apply(at(10), 100.0), 10.0 * (1.0 - (-1.0f64).exp()));
+    }
+
+    #[test]
+    fn ema_irregular_intervals() {
+        // Single long interval gives the same result as a few shorter ones with the same input
+        let mut regular = Filter::new(FilterType::Ema, Duration::from_secs(10));
+        regular.apply(at(0), 0.0);
+        regular.apply(at(5), 10.0);
+        regular.apply(at(10), 10.0);
+        let regular = regular.apply(at(20), 10.0);
+
+        let mut irregular = Filter::new(FilterType::Ema, Duration::from_secs(10));
+        irregular.apply(at(0), 0.0);
+        let irregular = irregular.apply(at(20), 10.0);
+
+        assert_close(regular, irregular);
+        assert_close(irregular, 10.0 * (1.0 - (-2.0f64).exp()));
+    }
+
+    #[test]
+    fn median_odd_and_even() {
+        let mut filter = Filter::new(FilterType::Median, Duration::from_secs(100));
+
+        assert_close(filter.apply(at(0), 1.0), 1.0);
+        assert_close(filter.apply(at(1), 3.0), 2.0);
+        assert_close(filter.apply(at(2), 2.0), 2.0);
+        // Average of the two middle values
+        assert_close(filter.apply(at(3), 10.0), 2.5);
+    }
+
+    #[test]
+    fn moving_average_window() {
+        let mut filter = Filter::new(FilterType::MovingAverage, Duration::from_secs(10));
+
+        assert_close(filter.apply(at(0), 1.0), 1.0);
+        assert_close(filter.apply(at(5), 3.0), 2.0);
+        // Samples taken more than a window before are dropped
+        assert_close(filter.apply(at(20), 5.0), 5.0);
+    }
+}
@@ -0,0 +1,218 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::sample::Sample;
+use super::time::SECONDS_IN_DAY;
+
+/// Battery health aggregated over one day
+#[derive(Debug, Clone)]
+pub struct HealthPoint {
+    // Days since UNIX epoch
+    day: u64,
+    state_of_health_sum: f64,
+    samples: u32,
+    energy_full: f64,
+    energy_full_design: f64,
+    cycle_count: Option<u32>,
+}
+
+impl HealthPoint {
+    /// Beginning of the day (UTC) this point is aggregated for
+    pub fn day(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.day * SECONDS_IN_DAY)
+    }
+
+    /// Average state of health during the day, in percents
+    pub fn state_of_health(&self) -> f64 {
+        self.state_of_health_sum / f64::from(self.samples)
+    }
+
+    /// Latest energy when full for that day, in watt-hours
+    pub fn energy_full(&self) -> f64 {
+        self.energy_full
+    }
+
+    /// Latest energy when full by design for that day, in watt-hours
+    pub fn energy_full_design(&self) -> f64 {
+        self.energy_full_design
+    }
+
+    pub fn cycle_count(&self) -> Option<u32> {
+        self.cycle_count
+    }
+}
+
+/// Long-term battery capacity fade, aggregated by days from the stored samples.
+///
+/// State of health trend is a linear regression over the daily averages,
+/// which is used to project when state of health will reach the `threshold`.
+#[derive(Debug)]
+pub struct HealthTrend {
+    threshold: f64,
+    points: Vec<HealthPoint>,
+}
+
+impl HealthTrend {
+    pub fn new(threshold: f64) -> HealthTrend {
+        HealthTrend {
+            threshold,
+            points: vec![],
+        }
+    }
+
+    /// Feed the sample into trend, samples are expected to be ordered by time
+    pub fn push(&mut self, sample: &Sample) {
+        let day = match sample.timestamp.duration_since(UNIX_EPOCH) {
+            Ok(since_epoch) => since_epoch.as_secs() / SECONDS_IN_DAY,
+            Err(_) => return,
+        };
+
+        match self.points.last_mut() {
+            Some(point) if point.day == day => {
+                point.state_of_health_sum += sample.state_of_health;
+                point.samples += 1;
+                point.energy_full = sample.energy_full;
+                point.energy_full_design = sample.energy_full_design;
+                point.cycle_count = sample.cycle_count.or(point.cycle_count);
+            }
+            Some(point) if point.day > day => {
+                trace!("Ignoring sample from the past for health trend: {:?}", sample.timestamp);
+            }
+            _ => self.points.push(HealthPoint {
+                day,
+                state_of_health_sum: sample.state_of_health,
+                samples: 1,
+                energy_full: sample.energy_full,
+                energy_full_design: sample.energy_full_design,
+                cycle_count: sample.cycle_count,
+            }),
+        }
+    }
+
+    /// State of health value, in percents, which is considered as a replacement point
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    /// Daily points, oldest one goes first
+    pub fn points(&self) -> &[HealthPoint] {
+        self.points.as_ref()
+    }
+
+    /// State of health change per day, in percents; negative value means that capacity is fading.
+    ///
+    /// At least two days of data are required.
+    pub fn slope(&self) -> Option<f64> {
+        self.regression().map(|(slope, _)| slope)
+    }
+
+    /// Day when state of health is expected to reach the threshold,
+    /// `None` is returned if there is not enough data or capacity is not fading at all
+    pub fn projected(&self) -> Option<SystemTime> {
+        let (slope, intercept) = self.regression()?;
+        if slope >= 0.0 {
+            return None;
+        }
+
+        let day = (self.threshold - intercept) / slope;
+        if !day.is_finite() || day < 0.0 {
+            return None;
+        }
+
+        Some(UNIX_EPOCH + Duration::from_secs((day * SECONDS_IN_DAY as f64) as u64))
+    }
+
+    /// Least squares fit for the daily state of health, returns slope and intercept
+    fn regression(&self) -> Option<(f64, f64)> {
+        if self.points.len() < 2 {
+            return None;
+        }
+
+        let count = self.points.len() as f64;
+        let x_mean = self.points.iter().map(|point| point.day as f64).sum::<f64>() / count;
+        let y_mean = self.points.iter().map(HealthPoint::state_of_health).sum::<f64>() / count;
+        let (mut xy, mut xx) = (0.0, 0.0);
+        for point in &self.points {
+            let x = point.day as f64 - x_mean;
+            xy += x * (point.state_of_health() - y_mean);
+            xx += x * x;
+        }
+
+        let slope = xy / xx;
+        Some((slope, y_mean - slope * x_mean))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::HealthTrend;
+    use crate::app::sample::fixtures::{at, sample};
+    use crate::app::sample::Sample;
+    use crate::app::time::SECONDS_IN_DAY;
+
+    /// Some day far enough from the epoch
+    const FIRST_DAY: u64 = 18_000;
+
+    fn day(day: u64) -> SystemTime {
+        at((FIRST_DAY + day) * SECONDS_IN_DAY)
+    }
+
+    fn health(timestamp: SystemTime, state_of_health: f64) -> Sample {
+        Sample {
+            state_of_health,
+            ..sample(timestamp)
+        }
+    }
+
+    fn trend(values: &[f64]) -> HealthTrend {
+        let mut trend = HealthTrend::new(80.0);
+        for (i, value) in values.iter().enumerate() {
+            trend.push(&health(day(i as u64), *value));
+        }
+
+        trend
+    }
+
+    #[test]
+    fn daily_average() {
+        let mut trend = HealthTrend::new(80.0);
+        trend.push(&health(day(0), 90.0));
+        trend.push(&health(day(0) + Duration::from_secs(3600), 92.0));
+        trend.push(&health(day(1), 89.0));
+        // Samples from the past are ignored
+        trend.push(&health(day(0), 10.0));
+
+        let points = trend.points();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].day(), day(0));
+        assert_eq!(points[0].state_of_health(), 91.0);
+        assert_eq!(points[1].state_of_health(), 89.0);
+    }
+
+    #[test]
+    fn not_enough_days() {
+        let trend = trend(&[90.0]);
+
+        assert_eq!(trend.slope(), None);
+        assert_eq!(trend.projected(), None);
+    }
+
+    #[test]
+    fn fading_projection() {
+        // One percent per day down from 90 %, so 80 % threshold is reached on the tenth day
+        let trend = trend(&[90.0, 89.0, 88.0]);
+
+        assert_eq!(trend.slope(), Some(-1.0));
+        assert_eq!(trend.projected(), Some(day(10)));
+    }
+
+    #[test]
+    fn no_fading() {
+        assert_eq!(trend(&[90.0, 90.0, 90.0]).projected(), None);
+
+        let growing = trend(&[88.0, 89.0, 90.0]);
+        assert_eq!(growing.slope(), Some(1.0));
+        assert_eq!(growing.projected(), None);
+    }
+}
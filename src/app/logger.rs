@@ -0,0 +1,128 @@
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::SystemTime;
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+use super::Config;
+use crate::{Result, ResultExt};
+
+/// Amount of the latest records kept for the log pane
+const KEPT_RECORDS: usize = 1000;
+
+/// Logger writing records to stderr and to the optional log file,
+/// while keeping the latest ones in memory for the log pane.
+///
+/// Stderr output is disabled while the interactive viewer is displayed,
+/// as it garbles the alternate screen.
+#[derive(Debug)]
+pub struct Logger {
+    // Only records from this module and its submodules are logged
+    module: &'static str,
+    level: LevelFilter,
+    stderr: AtomicBool,
+    file: Option<Mutex<File>>,
+    records: Mutex<VecDeque<String>>,
+}
+
+impl Logger {
+    /// Install logger for `module` records with the verbosity requested in `config`
+    pub fn init(module: &'static str, config: &Config) -> Result<&'static Logger> {
+        // Same verbosity levels as the ones used by `stderrlog` crate
+        let level = match config.verbosity() {
+            0 => LevelFilter::Error,
+            1 => LevelFilter::Warn,
+            2 => LevelFilter::Info,
+            3 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        };
+        let file = match config.log_file() {
+            Some(path) => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .context(|| format!("Unable to open log file {}", path.display()))?;
+                Some(Mutex::new(file))
+            }
+            None => None,
+        };
+
+        // Logger should live until the process exit, so it is leaked intentionally
+        let logger: &'static Logger = Box::leak(Box::new(Logger {
+            module,
+            level,
+            stderr: AtomicBool::new(true),
+            file,
+            records: Mutex::new(VecDeque::with_capacity(KEPT_RECORDS)),
+        }));
+        log::set_logger(logger)?;
+        log::set_max_level(level);
+
+        Ok(logger)
+    }
+
+    /// Enable or disable writing records to stderr
+    pub fn set_stderr(&self, enabled: bool) {
+        self.stderr.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Amount of records kept
+    pub fn count(&self) -> usize {
+        self.records.lock().unwrap_or_else(PoisonError::into_inner).len()
+    }
+
+    /// Latest records, from the oldest to the newest one
+    pub fn records(&self) -> Vec<String> {
+        let records = self.records.lock().unwrap_or_else(PoisonError::into_inner);
+        records.iter().cloned().collect()
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let target = metadata.target();
+        let is_module =
+            target == self.module || (target.starts_with(self.module) && target[self.module.len()..].starts_with("::"));
+
+        is_module && metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = format!(
+            "{} - {} - {}",
+            humantime::format_rfc3339_seconds(SystemTime::now()),
+            record.level(),
+            record.args()
+        );
+
+        // Write errors are ignored, as there is no other place left to report them to
+        if self.stderr.load(Ordering::SeqCst) {
+            let _ = writeln!(io::stderr(), "{}", line);
+        }
+        if let Some(ref file) = self.file {
+            let mut file = file.lock().unwrap_or_else(PoisonError::into_inner);
+            let _ = writeln!(file, "{}", line);
+        }
+
+        let mut records = self.records.lock().unwrap_or_else(PoisonError::into_inner);
+        if records.len() == KEPT_RECORDS {
+            records.pop_front();
+        }
+        records.push_back(line);
+    }
+
+    fn flush(&self) {
+        if let Some(ref file) = self.file {
+            let mut file = file.lock().unwrap_or_else(PoisonError::into_inner);
+            let _ = file.flush();
+        }
+    }
+}
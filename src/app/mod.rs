@@ -1,7 +1,23 @@
 mod application;
+mod commands;
 pub mod config;
+mod device;
+mod estimator;
 mod events;
+mod field;
+mod filter;
+mod health;
+mod logger;
+mod report;
+mod sample;
+mod session;
+mod snapshot;
+mod store;
+mod time;
 mod ui;
 
 pub use self::application::{init, Application};
+pub use self::commands::{export, get, list, watch};
 pub use self::config::Config;
+pub use self::logger::Logger;
+pub use self::report::report;
@@ -0,0 +1,416 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use battery::units::time::second;
+use battery::State;
+
+use super::device::{self, Device};
+use super::health::HealthTrend;
+use super::sample::Sample;
+use super::session::{Session, SessionTracker};
+use super::store::Store;
+use super::time;
+use super::Config;
+use crate::{Error, Result, ResultExt};
+
+/// How many of the latest sessions are included into report
+const REPORT_SESSIONS: usize = 20;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    pub fn arg_variants() -> [&'static str; 2] {
+        ["markdown", "html"]
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match () {
+            _ if s.eq_ignore_ascii_case("markdown") => Ok(ReportFormat::Markdown),
+            _ if s.eq_ignore_ascii_case("html") => Ok(ReportFormat::Html),
+            _ => Err(Error::Parse("report format", s.to_string())),
+        }
+    }
+}
+
+/// Render static report for all batteries installed into `output` file or into stdout.
+pub fn report(config: &Config, format: ReportFormat, output: Option<&Path>) -> Result<()> {
+    let manager = device::manager()?;
+    let now = SystemTime::now();
+
+    let mut document = Document::new(format, "Battery report");
+    document.paragraph(&format!("Generated at {}", humantime::format_rfc3339_seconds(now)));
+
+    for device in device::selected(&manager, config.batteries())? {
+        let battery = device.battery();
+        let sample = Sample::new(now, battery);
+        let mut history = History::new(config);
+        if config.store() {
+            history.load(&device);
+        }
+        // Current state is not stored yet, but it is the most recent one
+        history.push(&sample);
+
+        match battery.model() {
+            Some(model) => document.heading(&format!("Battery {}: {}", device.id(), model.trim())),
+            None => document.heading(&format!("Battery {}", device.id())),
+        }
+        device_section(&mut document, config, battery, &sample);
+        // Charge units are using the present voltage, as it is not stored for health points and sessions
+        capacity_section(&mut document, config, &history.health, sample.voltage);
+        sessions_section(&mut document, config, &history.sessions, sample.voltage);
+        runtime_section(&mut document, config, &history.sessions, &sample);
+    }
+
+    let content = document.finish();
+    match output {
+        Some(path) => {
+            File::create(path)
+                .and_then(|mut file| file.write_all(content.as_bytes()))
+                .context(|| format!("Unable to write report into {}", path.display()))?;
+            info!("Report was written into {}", path.display());
+        }
+        None => io::stdout().write_all(content.as_bytes())?,
+    }
+
+    Ok(())
+}
+
+/// Battery history restored from the store
+struct History {
+    health: HealthTrend,
+    sessions: SessionTracker,
+    // Expected interval between the stored samples
+    interval: Duration,
+}
+
+impl History {
+    fn new(config: &Config) -> History {
+        History {
+            health: HealthTrend::new(config.health_threshold()),
+            sessions: SessionTracker::default(),
+            interval: *config.delay(),
+        }
+    }
+
+    fn load(&mut self, device: &Device) {
+        let store = match Store::existing(device) {
+            Ok(Some(store)) => store,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Unable to open samples store for battery: {}", e);
+                return;
+            }
+        };
+
+        match store.samples() {
+            Ok(samples) => {
+                for sample in samples {
+                    self.push(&sample);
+                }
+            }
+            Err(e) => warn!("Unable to load samples from {}: {}", store.path().display(), e),
+        }
+    }
+
+    fn push(&mut self, sample: &Sample) {
+        self.health.push(sample);
+        self.sessions.update(
+            sample.timestamp,
+            sample.state,
+            sample.state_of_charge,
+            sample.energy,
+            sample.energy_rate,
+            self.interval,
+        );
+    }
+}
+
+fn device_section(document: &mut Document, config: &Config, battery: &battery::Battery, sample: &Sample) {
+    let energy = |value: f64| config.energy_units().format(value, sample.voltage);
+    let optional = |value: Option<&str>| value.unwrap_or("N/A").to_string();
+    let time = |value: Option<battery::units::Time>| match value {
+        Some(time) => humantime::format_duration(Duration::from_secs(time.get::<second>() as u64)).to_string(),
+        None => "N/A".to_string(),
+    };
+
+    let rows = vec![
+        vec!["Vendor".to_string(), optional(battery.vendor())],
+        vec!["Model".to_string(), optional(battery.model())],
+        vec!["S/N".to_string(), optional(battery.serial_number())],
+        vec!["Technology".to_string(), battery.technology().to_string()],
+        vec!["Charge state".to_string(), sample.state.to_string()],
+        vec![
+            "State of charge".to_string(),
+            format!("{:.2} %", sample.state_of_charge),
+        ],
+        vec!["Energy".to_string(), energy(sample.energy)],
+        vec!["Last full".to_string(), energy(sample.energy_full)],
+        vec!["Full design".to_string(), energy(sample.energy_full_design)],
+        vec![
+            "State of health".to_string(),
+            format!("{:.2} %", sample.state_of_health),
+        ],
+        vec![
+            "Energy rate".to_string(),
+            config.power_units().format(sample.energy_rate),
+        ],
+        vec!["Voltage".to_string(), format!("{:.2} V", sample.voltage)],
+        vec![
+            "Current".to_string(),
+            match sample.current() {
+                Some(current) => config.current_units().format(current),
+                None => "N/A".to_string(),
+            },
+        ],
+        vec![
+            "Temperature".to_string(),
+            match sample.temperature {
+                Some(temperature) => config.temperature_units().format(temperature),
+                None => "N/A".to_string(),
+            },
+        ],
+        vec![
+            "Cycles count".to_string(),
+            match sample.cycle_count {
+                Some(cycles) => cycles.to_string(),
+                None => "N/A".to_string(),
+            },
+        ],
+        vec!["Time to full".to_string(), time(battery.time_to_full())],
+        vec!["Time to empty".to_string(), time(battery.time_to_empty())],
+    ];
+
+    document.subheading("Device");
+    document.table(&["Property", "Value"], &rows);
+}
+
+fn capacity_section(document: &mut Document, config: &Config, health: &HealthTrend, voltage: f64) {
+    let units = config.energy_units();
+    document.subheading("Capacity history");
+
+    let rows = health
+        .points()
+        .iter()
+        .map(|point| {
+            vec![
+                time::date(point.day()),
+                units.format(point.energy_full(), voltage),
+                units.format(point.energy_full_design(), voltage),
+                format!("{:.2} %", point.state_of_health()),
+                match point.cycle_count() {
+                    Some(cycles) => cycles.to_string(),
+                    None => "N/A".to_string(),
+                },
+            ]
+        })
+        .collect::<Vec<_>>();
+    document.table(
+        &[
+            "Day (UTC)",
+            "Last full",
+            "Full design",
+            "State of health",
+            "Cycles count",
+        ],
+        &rows,
+    );
+
+    let trend = match health.slope() {
+        Some(slope) => format!("State of health is changing by {:+.3} % per week", slope * 7.0),
+        None => "Not enough data to calculate the state of health trend".to_string(),
+    };
+    let projected = match health.projected() {
+        Some(day) => format!(
+            "{:.0} % threshold is expected to be reached at {}",
+            health.threshold(),
+            time::date(day)
+        ),
+        None => format!("{:.0} % threshold is not expected to be reached", health.threshold()),
+    };
+    document.paragraph(&format!("{}. {}.", trend, projected));
+}
+
+fn sessions_section(document: &mut Document, config: &Config, sessions: &SessionTracker, voltage: f64) {
+    let power_units = config.power_units();
+    document.subheading("Recent sessions");
+
+    let rows = sessions
+        .current()
+        .into_iter()
+        .chain(sessions.finished())
+        .take(REPORT_SESSIONS)
+        .map(|session| {
+            let (soc_start, soc_end) = session.state_of_charge();
+            vec![
+                session.state().to_string(),
+                time::timestamp(session.started()),
+                time::timestamp(session.ended()),
+                time::minutes(session.duration()),
+                format!("{:.0} % → {:.0} %", soc_start, soc_end),
+                config.energy_units().format(session.energy(), voltage),
+                format!(
+                    "{} / {}",
+                    power_units.format(session.average_rate()),
+                    power_units.format(session.peak_rate())
+                ),
+            ]
+        })
+        .collect::<Vec<_>>();
+    document.table(
+        &[
+            "State",
+            "Started (UTC)",
+            "Ended (UTC)",
+            "Duration",
+            "State of charge",
+            "Energy",
+            "Avg / peak rate",
+        ],
+        &rows,
+    );
+}
+
+fn runtime_section(document: &mut Document, config: &Config, sessions: &SessionTracker, sample: &Sample) {
+    document.subheading("Estimated runtime");
+
+    // Average discharge rate over all known discharging sessions, weighted by their duration
+    let (energy, hours) = sessions
+        .current()
+        .into_iter()
+        .chain(sessions.finished())
+        .filter(|session| session.state() == State::Discharging)
+        .fold((0.0, 0.0), |(energy, hours), session: &Session| {
+            (
+                energy + session.energy(),
+                hours + time::seconds(session.duration()) / 3600.0,
+            )
+        });
+    let rate = if energy > 0.0 && hours > 0.0 {
+        energy / hours
+    } else if sample.state == State::Discharging {
+        sample.energy_rate.abs()
+    } else {
+        document.paragraph("No discharging history available to estimate the runtime.");
+        return;
+    };
+
+    let runtime = |capacity: f64| time::minutes(Duration::from_secs((capacity / rate * 3600.0) as u64));
+    let rows = vec![
+        vec!["Average discharge rate".to_string(), config.power_units().format(rate)],
+        vec![
+            "At full design capacity".to_string(),
+            runtime(sample.energy_full_design),
+        ],
+        vec!["At last full capacity".to_string(), runtime(sample.energy_full)],
+    ];
+    document.table(&["Runtime", ""], &rows);
+}
+
+/// Report content builder, which hides the output format details
+struct Document {
+    format: ReportFormat,
+    content: String,
+}
+
+impl Document {
+    fn new(format: ReportFormat, title: &str) -> Document {
+        let content = match format {
+            ReportFormat::Markdown => format!("# {}\n\n", title),
+            ReportFormat::Html => format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n\
+                 <style>table {{ border-collapse: collapse; }} th, td {{ border: 1px solid #aaa; \
+                 padding: 2px 8px; text-align: left; }}</style>\n</head>\n<body>\n<h1>{0}</h1>\n",
+                escape_html(title)
+            ),
+        };
+
+        Document {
+            format,
+            content,
+        }
+    }
+
+    fn heading(&mut self, text: &str) {
+        match self.format {
+            ReportFormat::Markdown => self.content.push_str(&format!("## {}\n\n", text)),
+            ReportFormat::Html => self.content.push_str(&format!("<h2>{}</h2>\n", escape_html(text))),
+        }
+    }
+
+    fn subheading(&mut self, text: &str) {
+        match self.format {
+            ReportFormat::Markdown => self.content.push_str(&format!("### {}\n\n", text)),
+            ReportFormat::Html => self.content.push_str(&format!("<h3>{}</h3>\n", escape_html(text))),
+        }
+    }
+
+    fn paragraph(&mut self, text: &str) {
+        match self.format {
+            ReportFormat::Markdown => self.content.push_str(&format!("{}\n\n", text)),
+            ReportFormat::Html => self.content.push_str(&format!("<p>{}</p>\n", escape_html(text))),
+        }
+    }
+
+    fn table(&mut self, header: &[&str], rows: &[Vec<String>]) {
+        if rows.is_empty() {
+            self.paragraph("No data available.");
+            return;
+        }
+
+        match self.format {
+            ReportFormat::Markdown => {
+                let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+                let escape = |cell: &str| cell.replace('|', "\\|");
+                self.content
+                    .push_str(&line(header.iter().map(|cell| escape(cell)).collect()));
+                self.content
+                    .push_str(&line(header.iter().map(|_| "---".to_string()).collect()));
+                for row in rows {
+                    self.content
+                        .push_str(&line(row.iter().map(|cell| escape(cell)).collect()));
+                }
+                self.content.push('\n');
+            }
+            ReportFormat::Html => {
+                let line = |tag: &str, cells: &mut dyn Iterator<Item = &str>| {
+                    let cells = cells
+                        .map(|cell| format!("<{0}>{1}</{0}>", tag, escape_html(cell)))
+                        .collect::<String>();
+                    format!("<tr>{}</tr>\n", cells)
+                };
+                self.content.push_str("<table>\n");
+                self.content.push_str(&line("th", &mut header.iter().cloned()));
+                for row in rows {
+                    self.content.push_str(&line("td", &mut row.iter().map(String::as_str)));
+                }
+                self.content.push_str("</table>\n");
+            }
+        }
+    }
+
+    fn finish(mut self) -> String {
+        if self.format == ReportFormat::Html {
+            self.content.push_str("</body>\n</html>\n");
+        }
+
+        self.content
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,92 @@
+use std::time::SystemTime;
+
+use battery::units::electric_potential::volt;
+use battery::units::energy::watt_hour;
+use battery::units::power::watt;
+use battery::units::ratio::percent;
+use battery::units::thermodynamic_temperature::kelvin;
+use battery::{Battery, State};
+
+pub const JOULES_IN_WATT_HOUR: f64 = 3600.0;
+pub const ZERO_CELSIUS_IN_KELVIN: f64 = 273.15;
+
+/// Battery readings taken at some moment.
+///
+/// Values are stored in the same units regardless of the `Units` chosen by user:
+/// percents, watt-hours, watts, volts and kelvins.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub timestamp: SystemTime,
+    pub state: State,
+    pub state_of_charge: f64,
+    pub state_of_health: f64,
+    pub energy: f64,
+    pub energy_full: f64,
+    pub energy_full_design: f64,
+    pub energy_rate: f64,
+    pub voltage: f64,
+    pub temperature: Option<f64>,
+    pub cycle_count: Option<u32>,
+}
+
+impl Sample {
+    pub fn new(timestamp: SystemTime, battery: &Battery) -> Sample {
+        Sample {
+            timestamp,
+            state: battery.state(),
+            state_of_charge: f64::from(battery.state_of_charge().get::<percent>()),
+            state_of_health: f64::from(battery.state_of_health().get::<percent>()),
+            energy: f64::from(battery.energy().get::<watt_hour>()),
+            energy_full: f64::from(battery.energy_full().get::<watt_hour>()),
+            energy_full_design: f64::from(battery.energy_full_design().get::<watt_hour>()),
+            energy_rate: f64::from(battery.energy_rate().get::<watt>()),
+            voltage: f64::from(battery.voltage().get::<volt>()),
+            temperature: battery.temperature().map(|temp| f64::from(temp.get::<kelvin>())),
+            cycle_count: battery.cycle_count(),
+        }
+    }
+
+    /// Current in amperes, derived from the energy rate and voltage,
+    /// since not all devices are reporting it
+    pub fn current(&self) -> Option<f64> {
+        if self.voltage > 0.0 {
+            Some(self.energy_rate / self.voltage)
+        } else {
+            None
+        }
+    }
+}
+
+/// Fixtures shared by the test modules
+#[cfg(test)]
+pub mod fixtures {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use battery::State;
+
+    use super::Sample;
+
+    /// Moment `seconds` after the UNIX epoch
+    pub fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    /// Discharging battery sample taken at `timestamp`, tests are changing the fields they need.
+    ///
+    /// Values are exactly representable with the precision used by the samples store.
+    pub fn sample(timestamp: SystemTime) -> Sample {
+        Sample {
+            timestamp,
+            state: State::Discharging,
+            state_of_charge: 87.5,
+            state_of_health: 95.25,
+            energy: 43.75,
+            energy_full: 50.0,
+            energy_full_design: 52.5,
+            energy_rate: 9.875,
+            voltage: 12.25,
+            temperature: Some(304.15),
+            cycle_count: Some(312),
+        }
+    }
+}
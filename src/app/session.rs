@@ -0,0 +1,263 @@
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime};
+
+use battery::State;
+
+/// How many finished sessions are kept
+const SESSIONS_LIMIT: usize = 100;
+
+/// Session is finished if there were no samples for that many sampling intervals,
+/// e.g. battop was not running, as battery might have been charged or discharged meanwhile
+const GAP_INTERVALS: u32 = 5;
+
+/// Shortest gap finishing the session, as samples might be taken less often than expected,
+/// e.g. when they were stored with a larger interval, compacted in store or battery refresh is retried
+const MIN_GAP: Duration = Duration::from_secs(10 * 60);
+
+/// Continuous period of battery charging or discharging
+#[derive(Debug, Clone)]
+pub struct Session {
+    state: State,
+    started: SystemTime,
+    ended: SystemTime,
+    // State of charge in percents
+    soc_start: f64,
+    soc_end: f64,
+    // Energy in watt-hours
+    energy_start: f64,
+    energy_end: f64,
+    // Energy rate in watts
+    rate_sum: f64,
+    rate_peak: f64,
+    samples: u32,
+}
+
+impl Session {
+    fn new(timestamp: SystemTime, state: State, soc: f64, energy: f64, rate: f64) -> Session {
+        Session {
+            state,
+            started: timestamp,
+            ended: timestamp,
+            soc_start: soc,
+            soc_end: soc,
+            energy_start: energy,
+            energy_end: energy,
+            rate_sum: rate,
+            rate_peak: rate,
+            samples: 1,
+        }
+    }
+
+    fn push(&mut self, timestamp: SystemTime, soc: f64, energy: f64, rate: f64) {
+        self.ended = timestamp;
+        self.soc_end = soc;
+        self.energy_end = energy;
+        self.rate_sum += rate;
+        self.rate_peak = self.rate_peak.max(rate);
+        self.samples += 1;
+    }
+
+    /// Check if there were no samples for longer than `max_gap` before the `timestamp`
+    fn is_gap(&self, timestamp: SystemTime, max_gap: Duration) -> bool {
+        match timestamp.duration_since(self.ended) {
+            Ok(gap) => gap > max_gap,
+            Err(_) => false,
+        }
+    }
+
+    /// Either `State::Charging` or `State::Discharging`
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn started(&self) -> SystemTime {
+        self.started
+    }
+
+    pub fn ended(&self) -> SystemTime {
+        self.ended
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.ended.duration_since(self.started).unwrap_or_default()
+    }
+
+    /// State of charge at the session start and end, in percents
+    pub fn state_of_charge(&self) -> (f64, f64) {
+        (self.soc_start, self.soc_end)
+    }
+
+    /// Energy delivered or absorbed during the session, in watt-hours
+    pub fn energy(&self) -> f64 {
+        (self.energy_end - self.energy_start).abs()
+    }
+
+    /// Average energy rate, in watts
+    pub fn average_rate(&self) -> f64 {
+        self.rate_sum / f64::from(self.samples)
+    }
+
+    /// Peak energy rate, in watts
+    pub fn peak_rate(&self) -> f64 {
+        self.rate_peak
+    }
+}
+
+/// Charge and discharge sessions log, sessions are detected from the battery state transitions
+#[derive(Debug, Default)]
+pub struct SessionTracker {
+    current: Option<Session>,
+    // Finished sessions, latest one goes first
+    finished: VecDeque<Session>,
+}
+
+impl SessionTracker {
+    /// Take a new sample, which is expected to be taken `interval` after the previous one
+    pub fn update(
+        &mut self,
+        timestamp: SystemTime,
+        state: State,
+        soc: f64,
+        energy: f64,
+        rate: f64,
+        interval: Duration,
+    ) {
+        let max_gap = (interval * GAP_INTERVALS).max(MIN_GAP);
+        match self.current.as_mut() {
+            Some(session) if session.state == state && !session.is_gap(timestamp, max_gap) => {
+                session.push(timestamp, soc, energy, rate);
+                return;
+            }
+            Some(_) => self.finish(),
+            None => {}
+        }
+
+        match state {
+            State::Charging | State::Discharging => {
+                debug!("New {} session started", state);
+                self.current = Some(Session::new(timestamp, state, soc, energy, rate));
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(&mut self) {
+        if let Some(session) = self.current.take() {
+            debug!("{} session finished after {:?}", session.state, session.duration());
+            self.finished.push_front(session);
+            self.finished.truncate(SESSIONS_LIMIT);
+        }
+    }
+
+    /// Session in progress, if battery is charging or discharging right now
+    pub fn current(&self) -> Option<&Session> {
+        self.current.as_ref()
+    }
+
+    /// Finished sessions, latest one goes first
+    pub fn finished(&self) -> impl Iterator<Item = &Session> {
+        self.finished.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use battery::State;
+
+    use super::SessionTracker;
+    use crate::app::sample::fixtures::at;
+
+    const INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Feed `(seconds, state)` samples, state of charge and energy are dropping by one each second
+    fn tracker(samples: &[(u64, State)], interval: Duration) -> SessionTracker {
+        let mut tracker = SessionTracker::default();
+        for (seconds, state) in samples.iter() {
+            let value = 100.0 - *seconds as f64;
+            tracker.update(at(*seconds), *state, value, value, 10.0, interval);
+        }
+
+        tracker
+    }
+
+    #[test]
+    fn state_transitions() {
+        let tracker = tracker(
+            &[
+                (0, State::Discharging),
+                (1, State::Discharging),
+                (2, State::Charging),
+                (3, State::Charging),
+                (4, State::Full),
+                (5, State::Discharging),
+            ],
+            INTERVAL,
+        );
+
+        let finished = tracker.finished().collect::<Vec<_>>();
+        assert_eq!(finished.len(), 2);
+        // Latest one goes first
+        assert_eq!(finished[0].state(), State::Charging);
+        assert_eq!((finished[0].started(), finished[0].ended()), (at(2), at(3)));
+        assert_eq!(finished[1].state(), State::Discharging);
+        assert_eq!((finished[1].started(), finished[1].ended()), (at(0), at(1)));
+
+        let current = tracker.current().unwrap();
+        assert_eq!(current.state(), State::Discharging);
+        assert_eq!(current.started(), at(5));
+    }
+
+    #[test]
+    fn unknown_state_is_not_a_session() {
+        let tracker = tracker(&[(0, State::Unknown), (1, State::Full)], INTERVAL);
+
+        assert!(tracker.current().is_none());
+        assert_eq!(tracker.finished().count(), 0);
+    }
+
+    #[test]
+    fn gap_shorter_than_minimum() {
+        // Ten minutes is the shortest gap, no matter how short the interval is
+        let tracker = tracker(&[(0, State::Discharging), (600, State::Discharging)], INTERVAL);
+
+        assert_eq!(tracker.finished().count(), 0);
+        assert_eq!(tracker.current().unwrap().duration(), Duration::from_secs(600));
+    }
+
+    #[test]
+    fn gap_longer_than_minimum() {
+        let tracker = tracker(&[(0, State::Discharging), (601, State::Discharging)], INTERVAL);
+
+        assert_eq!(tracker.finished().count(), 1);
+        assert_eq!(tracker.current().unwrap().started(), at(601));
+    }
+
+    #[test]
+    fn gap_in_intervals() {
+        // Five intervals of three minutes each are longer than the minimal gap
+        let interval = Duration::from_secs(180);
+        let tracker = tracker(
+            &[(0, State::Charging), (900, State::Charging), (1801, State::Charging)],
+            interval,
+        );
+
+        assert_eq!(tracker.finished().next().unwrap().ended(), at(900));
+        assert_eq!(tracker.current().unwrap().started(), at(1801));
+    }
+
+    #[test]
+    fn energy_and_rates() {
+        let mut tracker = SessionTracker::default();
+        tracker.update(at(0), State::Discharging, 80.0, 40.0, 10.0, INTERVAL);
+        tracker.update(at(1), State::Discharging, 79.0, 39.5, 30.0, INTERVAL);
+        tracker.update(at(2), State::Discharging, 78.0, 38.5, 20.0, INTERVAL);
+
+        let session = tracker.current().unwrap();
+        assert_eq!(session.state_of_charge(), (80.0, 78.0));
+        assert_eq!(session.energy(), 1.5);
+        assert_eq!(session.average_rate(), 20.0);
+        assert_eq!(session.peak_rate(), 30.0);
+    }
+}
@@ -0,0 +1,44 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use super::field::{Field, ALL_FIELDS};
+use super::sample::Sample;
+use super::store::Store;
+use super::ui::View;
+use crate::{Result, ResultExt};
+
+/// Write current readings of all `views` as tab-separated values
+/// into a new file in the data directory, returning the file path
+pub fn write(views: &[View]) -> Result<PathBuf> {
+    let directory = Store::directory()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Unable to determine data directory"))?;
+    fs::create_dir_all(&directory)?;
+
+    let now = SystemTime::now();
+    let path = directory.join(format!("snapshot-{}.tsv", humantime::format_rfc3339_seconds(now)));
+    let file = File::create(&path).context(|| format!("Unable to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+
+    let header = ALL_FIELDS.iter().map(Field::to_string).collect::<Vec<_>>();
+    writeln!(writer, "timestamp\tbattery\t{}", header.join("\t"))?;
+    for view in views.iter() {
+        let sample = Sample::new(now, view.battery());
+        let values = ALL_FIELDS
+            .iter()
+            .map(|field| field.value(view.battery(), &sample, view.config()).unwrap_or_default())
+            .collect::<Vec<_>>();
+
+        writeln!(
+            writer,
+            "{}\t{}\t{}",
+            humantime::format_rfc3339_seconds(now),
+            view.name(),
+            values.join("\t")
+        )?;
+    }
+    writer.flush()?;
+
+    Ok(path)
+}
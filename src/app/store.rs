@@ -0,0 +1,431 @@
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use battery::State;
+
+use super::device::Device;
+use super::sample::Sample;
+use crate::{Result, ResultExt};
+
+/// Size of the file tail read first when looking for the recent samples
+const TAIL_CHUNK: u64 = 64 * 1024;
+
+/// Log is compacted when it grows larger than that, which takes about two days with one second updates
+const COMPACT_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Samples taken during that period before compaction are kept as is
+const FULL_RESOLUTION_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Older samples are thinned out to one per that interval during compaction
+const COMPACTED_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Append-only log of the battery samples, one file per battery.
+///
+/// Log is growing by about 7 MB per day with one second updates, so it is compacted on start
+/// and then periodically while samples are appended, see `appended`:
+/// only one sample per `COMPACTED_INTERVAL` is kept for samples older than `FULL_RESOLUTION_PERIOD`,
+/// along with the ones where battery state changes, which is about 20 KB per day.
+/// Log file might be removed to drop the whole history.
+///
+/// Each sample is stored as a line of comma-separated values:
+/// timestamp (seconds since UNIX epoch), state, state of charge, state of health, energy,
+/// energy when full, energy when full by design, energy rate, voltage, temperature and cycle count.
+/// Last two values might be empty if device does not provide them.
+#[derive(Debug)]
+pub struct Store {
+    path: PathBuf,
+    file: File,
+    // Samples appended since the store was opened
+    appended: u64,
+}
+
+impl Store {
+    /// Directory where all the data is stored, `$XDG_DATA_HOME/battop/` or `~/.local/share/battop/`
+    pub fn directory() -> Option<PathBuf> {
+        let base = match env::var_os("XDG_DATA_HOME") {
+            Some(path) if !path.is_empty() => PathBuf::from(path),
+            _ => PathBuf::from(env::var_os("HOME")?).join(".local").join("share"),
+        };
+
+        Some(base.join("battop"))
+    }
+
+    /// Open the samples log for battery identified by `key`, e.g. its serial number
+    pub fn open(key: &str) -> Result<Store> {
+        let directory = Store::directory()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Unable to determine data directory"))?;
+        fs::create_dir_all(&directory).context(|| format!("Unable to create {}", directory.display()))?;
+
+        Store::open_in(&directory, key)
+    }
+
+    /// Open the samples log for battery `device`, see `key` for how it is identified
+    pub fn for_device(device: &Device) -> Result<Store> {
+        Store::open(&device_key(device))
+    }
+
+    /// Open the existing samples log for battery `device` for reading only, so nothing is created on disk.
+    ///
+    /// `None` is returned if there is no log yet. Samples can't be appended to the returned store.
+    pub fn existing(device: &Device) -> Result<Option<Store>> {
+        match Store::directory() {
+            Some(directory) => Store::open_existing(log_path(&directory, &device_key(device))),
+            None => Ok(None),
+        }
+    }
+
+    fn open_existing(path: PathBuf) -> Result<Option<Store>> {
+        match File::open(&path) {
+            Ok(file) => Ok(Some(Store {
+                path,
+                file,
+                appended: 0,
+            })),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+                debug!("There is no samples log at {}", path.display());
+                Ok(None)
+            }
+            Err(e) => Err(e).context(|| format!("Unable to open samples log {}", path.display())),
+        }
+    }
+
+    pub fn open_in(directory: &Path, key: &str) -> Result<Store> {
+        let path = log_path(directory, key);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .context(|| format!("Unable to open samples log {}", path.display()))?;
+        debug!("Samples log is opened at {}", path.display());
+
+        Ok(Store {
+            path,
+            file,
+            appended: 0,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    pub fn append(&mut self, sample: &Sample) -> Result<()> {
+        let line = format!("{}\n", format(sample));
+        self.file.write_all(line.as_bytes())?;
+        self.appended += 1;
+
+        Ok(())
+    }
+
+    /// Amount of samples appended since the store was opened
+    pub fn appended(&self) -> u64 {
+        self.appended
+    }
+
+    /// Load all samples taken at `since` moment or later.
+    ///
+    /// Only the file tail is read, which is growing until the older sample is found.
+    pub fn load_since(&self, since: SystemTime) -> Result<Vec<Sample>> {
+        let mut file = File::open(&self.path)?;
+        let length = file.metadata()?.len();
+        let mut chunk = TAIL_CHUNK;
+
+        loop {
+            let offset = length.saturating_sub(chunk);
+            file.seek(SeekFrom::Start(offset))?;
+            let mut content = String::new();
+            Read::by_ref(&mut file)
+                .take(length - offset)
+                .read_to_string(&mut content)?;
+
+            let mut lines = content.lines();
+            // Chunk is most likely starting in the middle of the line
+            if offset > 0 {
+                let _ = lines.next();
+            }
+            let samples = lines.filter_map(parse).collect::<Vec<_>>();
+
+            let found = match samples.first() {
+                Some(sample) => sample.timestamp < since,
+                None => false,
+            };
+            if found || offset == 0 {
+                return Ok(samples.into_iter().filter(|sample| sample.timestamp >= since).collect());
+            }
+
+            chunk *= 2;
+        }
+    }
+
+    /// Compact the log if it is too large, see `Store` docs
+    pub fn compact(&mut self) -> Result<()> {
+        let length = self.file.metadata()?.len();
+        if length <= COMPACT_SIZE {
+            return Ok(());
+        }
+
+        let since = SystemTime::now() - FULL_RESOLUTION_PERIOD;
+        let compacted = self.path.with_extension("log.tmp");
+        let (mut read, mut kept) = (0, 0);
+        {
+            let reader = BufReader::new(File::open(&self.path)?);
+            let mut writer = BufWriter::new(File::create(&compacted)?);
+            // Moment and state of the last sample kept
+            let mut last: Option<(SystemTime, State)> = None;
+            // Read errors are not ignored here, otherwise the rest of samples would be lost
+            for line in reader.lines() {
+                let sample = match parse(&line?) {
+                    Some(sample) => sample,
+                    None => continue,
+                };
+                read += 1;
+
+                let keep = sample.timestamp >= since
+                    || match last {
+                        Some((timestamp, state)) => {
+                            let elapsed = sample.timestamp.duration_since(timestamp);
+                            state != sample.state
+                                || elapsed.map(|elapsed| elapsed >= COMPACTED_INTERVAL).unwrap_or(true)
+                        }
+                        None => true,
+                    };
+                if keep {
+                    writeln!(writer, "{}", format(&sample))?;
+                    last = Some((sample.timestamp, sample.state));
+                    kept += 1;
+                }
+            }
+            writer.flush()?;
+        }
+        fs::rename(&compacted, &self.path).context(|| format!("Unable to replace {}", self.path.display()))?;
+        self.file = OpenOptions::new().append(true).open(&self.path)?;
+        info!(
+            "Samples log {} is compacted from {} to {} samples",
+            self.path.display(),
+            read,
+            kept
+        );
+
+        Ok(())
+    }
+
+    /// Iterate over all the samples stored, oldest one goes first
+    pub fn samples(&self) -> Result<impl Iterator<Item = Sample>> {
+        let reader = BufReader::new(File::open(&self.path)?);
+
+        Ok(reader
+            .lines()
+            .take_while(io::Result::is_ok)
+            .filter_map(io::Result::ok)
+            .filter_map(|line| parse(&line)))
+    }
+}
+
+fn device_key(device: &Device) -> String {
+    let battery = device.battery();
+
+    key(battery.serial_number(), battery.vendor(), battery.model(), &device.id())
+}
+
+/// Path of the samples log identified by `key` in the `directory`
+fn log_path(directory: &Path, key: &str) -> PathBuf {
+    // Serial numbers might contain pretty much anything, so only the safe subset is used for file name
+    let name = key
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect::<String>();
+
+    directory.join(format!("{}.log", name))
+}
+
+/// Key of the battery samples log, which is its serial number.
+///
+/// Vendor and model are used if serial number is missing or blank, along with the device `id`,
+/// so identical batteries without serial numbers are not mixed in one log.
+fn key(serial_number: Option<&str>, vendor: Option<&str>, model: Option<&str>, id: &str) -> String {
+    fn value(value: Option<&str>) -> Option<&str> {
+        value.map(str::trim).filter(|value| !value.is_empty())
+    }
+
+    match value(serial_number) {
+        Some(serial_number) => serial_number.to_string(),
+        None => format!(
+            "{}-{}-{}",
+            value(vendor).unwrap_or("unknown"),
+            value(model).unwrap_or("unknown"),
+            id
+        ),
+    }
+}
+
+fn format(sample: &Sample) -> String {
+    let timestamp = sample.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let temperature = match sample.temperature {
+        Some(temperature) => format!("{:.2}", temperature),
+        None => String::new(),
+    };
+    let cycle_count = match sample.cycle_count {
+        Some(cycle_count) => cycle_count.to_string(),
+        None => String::new(),
+    };
+
+    format!(
+        "{}.{:03},{},{:.2},{:.2},{:.4},{:.4},{:.4},{:.4},{:.4},{},{}",
+        timestamp.as_secs(),
+        timestamp.subsec_millis(),
+        sample.state,
+        sample.state_of_charge,
+        sample.state_of_health,
+        sample.energy,
+        sample.energy_full,
+        sample.energy_full_design,
+        sample.energy_rate,
+        sample.voltage,
+        temperature,
+        cycle_count,
+    )
+}
+
+/// Parse the stored line, malformed ones are ignored, since the log might be cut in the middle of write
+fn parse(line: &str) -> Option<Sample> {
+    let mut fields = line.split(',');
+    let mut next = || fields.next();
+    let number = |value: Option<&str>| value.and_then(|value| f64::from_str(value).ok());
+
+    let timestamp = number(next())?;
+    if timestamp < 0.0 {
+        return None;
+    }
+    let sample = Sample {
+        timestamp: UNIX_EPOCH + Duration::from_millis((timestamp * 1000.0) as u64),
+        state: next()?.parse().ok()?,
+        state_of_charge: number(next())?,
+        state_of_health: number(next())?,
+        energy: number(next())?,
+        energy_full: number(next())?,
+        energy_full_design: number(next())?,
+        energy_rate: number(next())?,
+        voltage: number(next())?,
+        temperature: match next()? {
+            "" => None,
+            value => Some(f64::from_str(value).ok()?),
+        },
+        cycle_count: match next()? {
+            "" => None,
+            value => Some(u32::from_str(value).ok()?),
+        },
+    };
+
+    Some(sample)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use battery::State;
+
+    use super::{format, key, parse, Store};
+    use crate::app::sample::fixtures::{at, sample};
+
+    /// Empty directory unique for the test
+    fn directory(name: &str) -> PathBuf {
+        let directory = env::temp_dir().join(format!("battop-test-{}-{}", process::id(), name));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+
+        directory
+    }
+
+    #[test]
+    fn serial_number_key() {
+        assert_eq!(key(Some(" 1234 "), Some("LGC"), Some("45N1011"), "BAT0"), "1234");
+        // Blank serial number is the same as a missing one
+        assert_eq!(
+            key(Some("  "), Some("LGC"), Some("45N1011"), "BAT0"),
+            "LGC-45N1011-BAT0"
+        );
+        assert_eq!(key(None, None, Some("45N1011"), "0"), "unknown-45N1011-0");
+    }
+
+    #[test]
+    fn identical_batteries_key() {
+        let first = key(None, Some("LGC"), Some("45N1011"), "BAT0");
+        let second = key(None, Some("LGC"), Some("45N1011"), "BAT1");
+
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn open_existing() {
+        let directory = directory("open-existing");
+        let path = directory.join("BAT0.log");
+
+        assert!(Store::open_existing(path.clone()).unwrap().is_none());
+        assert!(!path.exists());
+
+        Store::open_in(&directory, "BAT0")
+            .unwrap()
+            .append(&sample(at(10)))
+            .unwrap();
+        let store = Store::open_existing(path).unwrap().unwrap();
+        assert_eq!(store.samples().unwrap().count(), 1);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut expected = sample(UNIX_EPOCH + Duration::from_millis(1_560_000_000_123));
+        assert_eq!(parse(&format(&expected)), Some(expected.clone()));
+
+        expected.state = State::Charging;
+        expected.temperature = None;
+        expected.cycle_count = None;
+        assert_eq!(parse(&format(&expected)), Some(expected));
+    }
+
+    #[test]
+    fn malformed() {
+        let line = format(&sample(at(1_560_000_000)));
+        // Line cut in the middle of write misses the last fields
+        let truncated = &line[..line.rfind(',').unwrap()];
+
+        assert_eq!(parse(truncated), None);
+        assert_eq!(parse(""), None);
+        assert_eq!(parse(&line.replace("discharging", "leaking")), None);
+        assert_eq!(parse(&line.replace("87.50", "many")), None);
+    }
+
+    #[test]
+    fn load_since() {
+        let directory = directory("load-since");
+        let mut store = Store::open_in(&directory, "BAT0").unwrap();
+        // Large enough to span a few tail chunks
+        for seconds in 0..5000 {
+            store.append(&sample(at(seconds))).unwrap();
+        }
+        assert!(fs::metadata(store.path()).unwrap().len() > 4 * super::TAIL_CHUNK);
+
+        for since in &[0, 10, 2500, 4990, 5000] {
+            let samples = store.load_since(at(*since)).unwrap();
+            assert_eq!(samples.len() as u64, 5000 - since);
+            if let Some(first) = samples.first() {
+                assert_eq!(first.timestamp, at(*since));
+            }
+        }
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
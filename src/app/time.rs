@@ -0,0 +1,51 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const SECONDS_IN_DAY: u64 = 24 * 60 * 60;
+
+/// Duration in seconds with the fractional part, same as `Duration::as_secs_f64`,
+/// which is not available for the minimal supported Rust version
+pub fn seconds(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) / 1e9
+}
+
+/// Moment `period` before the `moment`, but not earlier than the UNIX epoch.
+///
+/// Plain subtraction panics for long periods, e.g. `--history 1000years`,
+/// and `SystemTime::checked_sub` is not available for the minimal supported Rust version.
+pub fn earlier(moment: SystemTime, period: Duration) -> SystemTime {
+    let since_epoch = moment.duration_since(UNIX_EPOCH).unwrap_or_default();
+
+    UNIX_EPOCH + since_epoch.checked_sub(period).unwrap_or_default()
+}
+
+/// Date part of the RFC 3339 timestamp
+pub fn date(timestamp: SystemTime) -> String {
+    humantime::format_rfc3339_seconds(timestamp).to_string()[..10].to_string()
+}
+
+/// Date, hours and minutes part of the RFC 3339 timestamp
+pub fn timestamp(timestamp: SystemTime) -> String {
+    humantime::format_rfc3339_seconds(timestamp)
+        .to_string()
+        .replacen('T', " ", 1)[..16]
+        .to_string()
+}
+
+/// Duration rounded down to minutes, seconds are just a noise here
+pub fn minutes(duration: Duration) -> String {
+    humantime::format_duration(Duration::from_secs(duration.as_secs() / 60 * 60)).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::earlier;
+    use crate::app::sample::fixtures::at;
+
+    #[test]
+    fn earlier_than_epoch() {
+        assert_eq!(earlier(at(100), Duration::from_secs(40)), at(60));
+        assert_eq!(earlier(at(100), Duration::from_secs(u64::max_value())), UNIX_EPOCH);
+    }
+}
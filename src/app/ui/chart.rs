@@ -1,22 +1,59 @@
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 use battery::units::electric_potential::volt;
-use battery::units::power::watt;
-use battery::units::thermodynamic_temperature::{degree_celsius, kelvin};
+use battery::units::ratio::percent;
 use battery::units::Unit;
 use battery::State;
-use itertools::{Itertools, MinMaxResult};
 
-use super::Units;
+use super::{History, Statistics};
+use crate::app::filter::FilterType;
+use crate::app::sample::Sample;
+use crate::app::time;
 use crate::app::Config;
-
-const RESOLUTION: usize = 512;
+use crate::Error;
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum ChartType {
-    Voltage,
+    StateOfCharge,
+    Energy,
     EnergyRate,
+    Voltage,
+    Current,
     Temperature,
+    StateOfHealth,
+}
+
+impl ChartType {
+    pub fn arg_variants() -> [&'static str; 7] {
+        [
+            "state-of-charge",
+            "energy",
+            "energy-rate",
+            "voltage",
+            "current",
+            "temperature",
+            "state-of-health",
+        ]
+    }
+}
+
+impl FromStr for ChartType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match () {
+            _ if s.eq_ignore_ascii_case("state-of-charge") => Ok(ChartType::StateOfCharge),
+            _ if s.eq_ignore_ascii_case("energy") => Ok(ChartType::Energy),
+            _ if s.eq_ignore_ascii_case("energy-rate") => Ok(ChartType::EnergyRate),
+            _ if s.eq_ignore_ascii_case("voltage") => Ok(ChartType::Voltage),
+            _ if s.eq_ignore_ascii_case("current") => Ok(ChartType::Current),
+            _ if s.eq_ignore_ascii_case("temperature") => Ok(ChartType::Temperature),
+            _ if s.eq_ignore_ascii_case("state-of-health") => Ok(ChartType::StateOfHealth),
+            _ => Err(Error::Parse("chart type", s.to_string())),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -27,14 +64,19 @@ pub struct ChartData {
 
     battery_state: State,
 
-    points: Vec<(f64, f64)>,
-    value_latest: f64,
-    value_min: f64,
-    value_max: f64,
+    history: History,
+    // Filtered values, available for energy rate chart only if filter is enabled
+    smoothed: Option<History>,
 }
 
 impl ChartData {
     pub fn new(config: Arc<Config>, chart_type: ChartType) -> Self {
+        let history = History::default();
+        let smoothed = match chart_type {
+            ChartType::EnergyRate if config.filter() != FilterType::None => Some(History::default()),
+            _ => None,
+        };
+
         ChartData {
             config,
             chart_type,
@@ -42,109 +84,204 @@ impl ChartData {
 
             battery_state: State::Unknown,
 
-            points: Vec::with_capacity(256),
-            value_latest: 0.0,
-            value_min: 100.0,
-            value_max: 0.0,
+            history,
+            smoothed,
         }
     }
 
-    pub fn enabled(&mut self, value: bool) {
-        self.enabled = value;
+    /// Take a new `sample`.
+    ///
+    /// Chart is disabled if battery is not able to provide the required value.
+    /// `smoothed_rate` is a filtered energy rate value, used by the energy rate chart only.
+    pub fn update(&mut self, sample: &Sample, smoothed_rate: Option<f64>) {
+        self.battery_state = sample.state;
+
+        let value = match self.chart_type {
+            ChartType::StateOfCharge => Some(sample.state_of_charge),
+            ChartType::Energy => self.config.energy_units().convert(sample.energy, sample.voltage),
+            ChartType::EnergyRate => Some(self.config.power_units().convert(sample.energy_rate)),
+            ChartType::Voltage => Some(sample.voltage),
+            ChartType::Current => sample
+                .current()
+                .map(|current| self.config.current_units().convert(current)),
+            ChartType::Temperature => sample
+                .temperature
+                .map(|temp| self.config.temperature_units().convert(temp)),
+            ChartType::StateOfHealth => Some(sample.state_of_health),
+        };
+
+        match value {
+            Some(value) => {
+                Self::push(&mut self.history, sample.timestamp, value, &self.config);
+                self.enabled = true;
+            }
+            None => self.enabled = false,
+        }
+
+        if let (Some(smoothed), Some(value)) = (self.smoothed.as_mut(), smoothed_rate) {
+            let value = self.config.power_units().convert(value);
+            Self::push(smoothed, sample.timestamp, value, &self.config);
+        }
     }
 
-    pub fn battery_state(&mut self) -> &mut State {
-        &mut self.battery_state
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
     }
 
-    #[allow(clippy::cast_lossless)]
-    pub fn push<T>(&mut self, value: T)
+    fn push<T>(history: &mut History, timestamp: SystemTime, value: T, config: &Config)
     where
         T: Into<f64>,
     {
-        let value = value.into();
-
-        if self.points.len() == RESOLUTION {
-            self.points.remove(0);
-        }
-        for (x, _) in self.points.iter_mut() {
-            *x -= 0.5;
-        }
-
-        self.value_latest = value;
-
-        self.points.push((RESOLUTION as f64 / 2.0, value));
-        match self.points.iter().minmax_by_key(|(_, y)| y) {
-            MinMaxResult::MinMax((_, min), (_, max)) => {
-                self.value_min = *min;
-                self.value_max = *max;
-            }
-            MinMaxResult::OneElement((_, el)) => {
-                self.value_min = *el;
-                self.value_max = *el;
-            }
-            _ => {}
-        }
+        history.push(timestamp, value.into());
+        history.truncate(*config.history());
     }
 
     // Texts and titles
 
     pub fn title(&self) -> &str {
         match self.chart_type {
-            ChartType::Voltage => "Voltage",
+            ChartType::StateOfCharge => "State of charge",
+            ChartType::Energy => "Energy",
             ChartType::EnergyRate => match self.battery_state {
                 State::Charging => "Charging with",
                 State::Discharging => "Discharging with",
                 _ => "Consumption",
             },
+            ChartType::Voltage => "Voltage",
+            ChartType::Current => "Current",
             ChartType::Temperature => "Temperature",
+            ChartType::StateOfHealth => "State of health",
         }
     }
 
-    /// Current value formatted with proper units
+    /// Current value formatted with proper units, filtered one is preferred if available
     pub fn current(&self) -> String {
-        if self.enabled {
-            match self.chart_type {
-                ChartType::Voltage => format!("{:.2} {}", self.value_latest, volt::abbreviation()),
-                ChartType::EnergyRate => format!("{:.2} {}", self.value_latest, watt::abbreviation()),
-                ChartType::Temperature => match self.config.units() {
-                    Units::Human => format!("{:.2} {}", self.value_latest, degree_celsius::abbreviation()),
-                    Units::Si => format!("{:.2} {}", self.value_latest, kelvin::abbreviation()),
-                },
-            }
-        } else {
-            "NOT AVAILABLE".to_string()
+        let history = self.smoothed.as_ref().unwrap_or(&self.history);
+        match history.latest() {
+            Some((_, value)) if self.enabled => self.format(value),
+            _ => "NOT AVAILABLE".to_string(),
         }
     }
 
+    /// Format any value of this chart with proper units
+    pub fn format(&self, value: f64) -> String {
+        format!("{:.2} {}", value, self.y_title())
+    }
+
     // Data
 
-    pub fn points(&self) -> &[(f64, f64)] {
-        self.points.as_ref()
+    /// Chart points for the `window` period ending with the latest sample.
+    ///
+    /// X coordinate is an amount of seconds since the latest sample, which makes it always negative.
+    pub fn points(&self, window: Duration) -> Vec<(f64, f64)> {
+        self.visible(window)
+            .map(|(timestamp, value)| (self.x(*timestamp), *value))
+            .collect()
+    }
+
+    /// Same as `points`, but for the filtered values; empty if there are no filtered values
+    pub fn smoothed_points(&self, window: Duration) -> Vec<(f64, f64)> {
+        let (smoothed, since) = match (self.smoothed.as_ref(), self.history.latest()) {
+            (Some(smoothed), Some((latest, _))) => (smoothed, time::earlier(latest, window)),
+            _ => return vec![],
+        };
+
+        smoothed
+            .iter_since(since)
+            .map(|(timestamp, value)| (self.x(*timestamp), *value))
+            .collect()
+    }
+
+    /// Statistics for the samples in the `window` period ending with the latest sample
+    pub fn statistics(&self, window: Duration) -> Option<Statistics> {
+        Statistics::from_values(self.visible(window).map(|(_, value)| *value))
+    }
+
+    fn visible(&self, window: Duration) -> impl Iterator<Item = &(SystemTime, f64)> {
+        let since = match self.history.latest() {
+            Some((timestamp, _)) => time::earlier(timestamp, window),
+            // Empty history, so there is nothing to look for anyway
+            None => SystemTime::now(),
+        };
+
+        self.history.iter_since(since)
+    }
+
+    pub fn len(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Sample taken closest to the `moment`, as an `(x, y)` point and the moment it was actually taken
+    pub fn sample(&self, moment: SystemTime) -> Option<((f64, f64), SystemTime)> {
+        let (timestamp, value) = self.history.nearest(moment)?;
+
+        Some(((self.x(timestamp), value), timestamp))
+    }
+
+    pub fn latest(&self) -> Option<SystemTime> {
+        self.history.latest().map(|(timestamp, _)| timestamp)
+    }
+
+    /// Moment of the latest sample taken before the `moment`
+    pub fn previous(&self, moment: SystemTime) -> Option<SystemTime> {
+        self.history.before(moment).map(|(timestamp, _)| timestamp)
+    }
+
+    /// Moment of the earliest sample taken after the `moment`
+    pub fn next(&self, moment: SystemTime) -> Option<SystemTime> {
+        self.history.after(moment).map(|(timestamp, _)| timestamp)
+    }
+
+    /// Moment of the sample taken closest to `age` before the latest one
+    pub fn moment(&self, age: Duration) -> Option<SystemTime> {
+        let latest = self.latest()?;
+        let (timestamp, _) = self.history.nearest(time::earlier(latest, age))?;
+
+        Some(timestamp)
     }
 
     // X scale
 
-    pub fn x_bounds(&self) -> [f64; 2] {
-        [0.0, 256.0]
+    /// X coordinate for the sample taken at `timestamp`
+    fn x(&self, timestamp: SystemTime) -> f64 {
+        match self.history.latest() {
+            Some((latest, _)) => match latest.duration_since(timestamp) {
+                Ok(age) => -time::seconds(age),
+                Err(_) => 0.0,
+            },
+            None => 0.0,
+        }
+    }
+
+    pub fn x_bounds(&self, window: Duration) -> [f64; 2] {
+        [-time::seconds(window), 0.0]
+    }
+
+    pub fn x_labels(&self, window: Duration) -> Vec<String> {
+        let half = Duration::from_secs(window.as_secs() / 2);
+        vec![
+            format!("-{}", humantime::format_duration(window)),
+            format!("-{}", humantime::format_duration(half)),
+            "now".to_string(),
+        ]
     }
 
     // Y scale
 
     pub fn y_title(&self) -> &str {
         match self.chart_type {
+            ChartType::StateOfCharge | ChartType::StateOfHealth => percent::abbreviation(),
+            ChartType::Energy => self.config.energy_units().abbreviation(),
+            ChartType::EnergyRate => self.config.power_units().abbreviation(),
             ChartType::Voltage => volt::abbreviation(),
-            ChartType::EnergyRate => watt::abbreviation(),
-            ChartType::Temperature => match self.config.units() {
-                Units::Human => degree_celsius::abbreviation(),
-                Units::Si => kelvin::abbreviation(),
-            },
+            ChartType::Current => self.config.current_units().abbreviation(),
+            ChartType::Temperature => self.config.temperature_units().abbreviation(),
         }
     }
 
     fn y_lower(&self) -> f64 {
         if self.enabled {
-            let mut value = (self.value_min - 1.0).floor();
+            let mut value = (self.history.min().unwrap_or(0.0) - 1.0).floor();
             if value < 0.0 {
                 value = -1.0;
             }
@@ -156,7 +293,7 @@ impl ChartData {
 
     fn y_upper(&self) -> f64 {
         if self.enabled {
-            (self.value_max + 1.0).ceil()
+            (self.history.max().unwrap_or(0.0) + 1.0).ceil()
         } else {
             0.0
         }
@@ -170,3 +307,29 @@ impl ChartData {
         [self.y_lower(), self.y_upper()]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use structopt::StructOpt;
+
+    use super::{ChartData, ChartType};
+    use crate::app::sample::fixtures::{at, sample};
+    use crate::app::Config;
+
+    #[test]
+    fn samples_faster_than_delay() {
+        // Samples logged each second are restored into the chart with a longer delay
+        let config = Config::from_iter(&["battop", "--delay", "5", "--history", "1m"]);
+        let mut chart = ChartData::new(Arc::new(config), ChartType::Voltage);
+        for seconds in 0..=120 {
+            chart.update(&sample(at(seconds)), None);
+        }
+
+        let points = chart.points(Duration::from_secs(60));
+        assert_eq!(points.len(), 61);
+        assert_eq!(chart.moment(Duration::from_secs(60)), Some(at(60)));
+    }
+}
@@ -0,0 +1,92 @@
+use std::cmp::Ordering;
+
+use itertools::{Itertools, MinMaxResult};
+
+/// Reduce amount of chart `points` to fit into `buckets` columns.
+///
+/// X `bounds` are split into `buckets` equal ranges and only the points with minimum and maximum values
+/// are kept for each range, in the same order they were, so the spikes are still visible on the chart,
+/// while the renderer is not fed with thousands of the points which will end up in the same Braille dot anyway.
+pub fn min_max(points: &[(f64, f64)], bounds: [f64; 2], buckets: usize) -> Vec<(f64, f64)> {
+    let [lower, upper] = bounds;
+    if points.len() <= buckets * 2 || buckets == 0 || upper <= lower {
+        return points.to_vec();
+    }
+
+    let width = (upper - lower) / buckets as f64;
+    let bucket = |x: f64| (((x - lower) / width) as usize).min(buckets - 1);
+    let by_value = |a: &&(f64, f64), b: &&(f64, f64)| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal);
+
+    let mut result = Vec::with_capacity(buckets * 2);
+    for (_, group) in &points.iter().group_by(|(x, _)| bucket(*x)) {
+        match group.minmax_by(by_value) {
+            MinMaxResult::OneElement(point) => result.push(*point),
+            MinMaxResult::MinMax(min, max) => {
+                if min.0 <= max.0 {
+                    result.push(*min);
+                    result.push(*max);
+                } else {
+                    result.push(*max);
+                    result.push(*min);
+                }
+            }
+            MinMaxResult::NoElements => {}
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::min_max;
+
+    #[test]
+    fn few_points_are_kept() {
+        let points = [(0.0, 1.0), (1.0, 2.0), (2.0, 3.0)];
+
+        assert_eq!(min_max(&points, [0.0, 2.0], 2), points.to_vec());
+        assert_eq!(min_max(&points, [0.0, 2.0], 0), points.to_vec());
+    }
+
+    #[test]
+    fn order_is_preserved() {
+        // Maximum goes before minimum in the first bucket and after it in the second one
+        let points = [
+            (0.0, 5.0),
+            (1.0, 9.0),
+            (2.0, 1.0),
+            (3.0, 4.0),
+            (5.0, 4.0),
+            (6.0, 0.0),
+            (7.0, 8.0),
+            (8.0, 3.0),
+        ];
+
+        assert_eq!(
+            min_max(&points, [0.0, 10.0], 2),
+            vec![(1.0, 9.0), (2.0, 1.0), (6.0, 0.0), (7.0, 8.0)]
+        );
+    }
+
+    #[test]
+    fn bucket_edges() {
+        // Point at the bucket start belongs to it, point at the upper bound belongs to the last bucket
+        let points = [(0.0, 1.0), (1.0, 2.0), (2.0, 3.0), (2.5, 0.0), (3.0, 7.0), (4.0, 5.0)];
+
+        assert_eq!(
+            min_max(&points, [0.0, 4.0], 2),
+            vec![(0.0, 1.0), (1.0, 2.0), (2.5, 0.0), (3.0, 7.0)]
+        );
+    }
+
+    #[test]
+    fn single_point_bucket() {
+        let points = [(0.0, 1.0), (0.5, 2.0), (1.0, 3.0), (1.5, 0.0), (3.5, 4.0)];
+
+        assert_eq!(
+            min_max(&points, [0.0, 4.0], 2),
+            vec![(1.0, 3.0), (1.5, 0.0), (3.5, 4.0)]
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime};
+
+/// Buffer of the timestamped chart samples.
+///
+/// Buffer grows on demand and is bounded by the time period only, see `truncate`,
+/// as samples might be taken more often than the updates interval, e.g. on forced refreshes
+/// or when history is restored from the samples taken with a shorter interval.
+///
+/// Minimum and maximum values are tracked incrementally with a pair of monotonic deques,
+/// so both pushing a new sample and querying the extremes are amortized O(1),
+/// no matter how long the history is.
+#[derive(Debug, Default)]
+pub struct History {
+    samples: VecDeque<(SystemTime, f64)>,
+    // Sequence number of the next pushed sample
+    sequence: u64,
+    // Candidates for the minimum value as a `(sequence, value)` pairs, values are ascending
+    minimums: VecDeque<(u64, f64)>,
+    // Candidates for the maximum value as a `(sequence, value)` pairs, values are descending
+    maximums: VecDeque<(u64, f64)>,
+}
+
+impl History {
+    pub fn push(&mut self, timestamp: SystemTime, value: f64) {
+        while let Some((_, min)) = self.minimums.back() {
+            if *min > value {
+                self.minimums.pop_back();
+            } else {
+                break;
+            }
+        }
+        self.minimums.push_back((self.sequence, value));
+
+        while let Some((_, max)) = self.maximums.back() {
+            if *max < value {
+                self.maximums.pop_back();
+            } else {
+                break;
+            }
+        }
+        self.maximums.push_back((self.sequence, value));
+
+        self.samples.push_back((timestamp, value));
+        self.sequence += 1;
+    }
+
+    /// Drop all samples taken earlier than `period` before the latest one
+    pub fn truncate(&mut self, period: Duration) {
+        let latest = match self.latest() {
+            Some((timestamp, _)) => timestamp,
+            None => return,
+        };
+
+        while let Some((timestamp, _)) = self.samples.front() {
+            match latest.duration_since(*timestamp) {
+                Ok(age) if age > period => self.pop_front(),
+                _ => break,
+            }
+        }
+    }
+
+    fn pop_front(&mut self) {
+        if self.samples.pop_front().is_none() {
+            return;
+        }
+
+        // Sequence number of the sample which was just removed
+        let removed = self.sequence - self.samples.len() as u64 - 1;
+        if let Some((sequence, _)) = self.minimums.front() {
+            if *sequence == removed {
+                self.minimums.pop_front();
+            }
+        }
+        if let Some((sequence, _)) = self.maximums.front() {
+            if *sequence == removed {
+                self.maximums.pop_front();
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Sample at `index`, counting from the oldest one
+    pub fn get(&self, index: usize) -> Option<(SystemTime, f64)> {
+        self.samples.get(index).cloned()
+    }
+
+    pub fn latest(&self) -> Option<(SystemTime, f64)> {
+        self.samples.back().cloned()
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.minimums.front().map(|(_, value)| *value)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.maximums.front().map(|(_, value)| *value)
+    }
+
+    /// Iterate over the samples taken at `since` moment or later
+    pub fn iter_since(&self, since: SystemTime) -> impl Iterator<Item = &(SystemTime, f64)> {
+        self.samples.iter().skip(self.position(since))
+    }
+
+    /// Sample taken closest to the `moment`
+    pub fn nearest(&self, moment: SystemTime) -> Option<(SystemTime, f64)> {
+        let index = self.position(moment);
+        let before = index.checked_sub(1).and_then(|index| self.get(index));
+        match (before, self.get(index)) {
+            (Some(before), Some(after)) => {
+                let since_before = moment.duration_since(before.0).unwrap_or_default();
+                let until_after = after.0.duration_since(moment).unwrap_or_default();
+                if since_before < until_after {
+                    Some(before)
+                } else {
+                    Some(after)
+                }
+            }
+            (before, after) => before.or(after),
+        }
+    }
+
+    /// Latest sample taken before the `moment`
+    pub fn before(&self, moment: SystemTime) -> Option<(SystemTime, f64)> {
+        self.position(moment).checked_sub(1).and_then(|index| self.get(index))
+    }
+
+    /// Earliest sample taken after the `moment`
+    pub fn after(&self, moment: SystemTime) -> Option<(SystemTime, f64)> {
+        self.iter_since(moment)
+            .find(|(timestamp, _)| *timestamp > moment)
+            .cloned()
+    }
+
+    /// Index of the first sample taken at `moment` or later
+    fn position(&self, moment: SystemTime) -> usize {
+        // Samples are ordered by time, so it can be found with binary search
+        let (mut lower, mut upper) = (0, self.samples.len());
+        while lower < upper {
+            let middle = lower + (upper - lower) / 2;
+            if self.samples[middle].0 < moment {
+                lower = middle + 1;
+            } else {
+                upper = middle;
+            }
+        }
+
+        lower
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::History;
+    use crate::app::sample::fixtures::at;
+
+    fn history(values: &[f64]) -> History {
+        let mut history = History::default();
+        for (i, value) in values.iter().enumerate() {
+            history.push(at(i as u64), *value);
+        }
+
+        history
+    }
+
+    #[test]
+    fn empty() {
+        let history = History::default();
+
+        assert_eq!(history.len(), 0);
+        assert_eq!(history.min(), None);
+        assert_eq!(history.max(), None);
+        assert_eq!(history.nearest(at(0)), None);
+    }
+
+    #[test]
+    fn min_max() {
+        let history = history(&[3.0, 1.0, 4.0, 1.0, 5.0, 9.0, 2.0]);
+
+        assert_eq!(history.min(), Some(1.0));
+        assert_eq!(history.max(), Some(9.0));
+    }
+
+    #[test]
+    fn min_max_after_eviction() {
+        // Both extremes are evicted as the oldest samples
+        let mut history = history(&[1.0, 9.0, 5.0]);
+        assert_eq!((history.min(), history.max()), (Some(1.0), Some(9.0)));
+
+        history.push(at(3), 6.0);
+        history.truncate(Duration::from_secs(2));
+        assert_eq!(history.len(), 3);
+        assert_eq!((history.min(), history.max()), (Some(5.0), Some(9.0)));
+
+        history.push(at(4), 7.0);
+        history.truncate(Duration::from_secs(2));
+        assert_eq!((history.min(), history.max()), (Some(5.0), Some(7.0)));
+
+        history.push(at(5), 8.0);
+        history.truncate(Duration::from_secs(2));
+        assert_eq!((history.min(), history.max()), (Some(6.0), Some(8.0)));
+    }
+
+    #[test]
+    fn min_max_with_duplicates() {
+        // Equal values are all kept as candidates, so evicting one of them keeps the others
+        let mut history = history(&[2.0, 2.0]);
+        history.push(at(2), 3.0);
+        history.truncate(Duration::from_secs(1));
+
+        assert_eq!((history.min(), history.max()), (Some(2.0), Some(3.0)));
+    }
+
+    #[test]
+    fn truncate() {
+        let mut history = history(&[1.0, 9.0, 5.0, 4.0, 6.0]);
+
+        history.truncate(Duration::from_secs(2));
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.get(0), Some((at(2), 5.0)));
+        assert_eq!((history.min(), history.max()), (Some(4.0), Some(6.0)));
+
+        // Evicted samples are not counted anymore when the new ones are pushed
+        history.push(at(5), 3.0);
+        assert_eq!((history.min(), history.max()), (Some(3.0), Some(6.0)));
+    }
+
+    #[test]
+    fn samples_faster_than_interval() {
+        // Nothing is evicted until samples are older than the period, no matter how many there are
+        let mut history = History::default();
+        for seconds in 0..1000 {
+            history.push(at(seconds), seconds as f64);
+            history.truncate(Duration::from_secs(600));
+        }
+
+        assert_eq!(history.len(), 601);
+        assert_eq!(history.get(0), Some((at(399), 399.0)));
+        assert_eq!((history.min(), history.max()), (Some(399.0), Some(999.0)));
+    }
+
+    #[test]
+    fn iter_since() {
+        let history = history(&[1.0, 2.0, 3.0, 4.0]);
+        let values = |since| history.iter_since(since).map(|(_, value)| *value).collect::<Vec<_>>();
+
+        assert_eq!(values(at(0)), vec![1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(values(at(2)), vec![3.0, 4.0]);
+        assert_eq!(values(at(10)), Vec::<f64>::new());
+    }
+
+    #[test]
+    fn nearest() {
+        let mut history = History::default();
+        history.push(at(10), 1.0);
+        history.push(at(20), 2.0);
+
+        assert_eq!(history.nearest(at(0)), Some((at(10), 1.0)));
+        assert_eq!(history.nearest(at(14)), Some((at(10), 1.0)));
+        assert_eq!(history.nearest(at(16)), Some((at(20), 2.0)));
+        assert_eq!(history.nearest(at(30)), Some((at(20), 2.0)));
+    }
+
+    #[test]
+    fn before_after() {
+        let history = history(&[1.0, 2.0, 3.0]);
+
+        assert_eq!(history.before(at(1)), Some((at(0), 1.0)));
+        assert_eq!(history.before(at(0)), None);
+        assert_eq!(history.after(at(1)), Some((at(2), 3.0)));
+        assert_eq!(history.after(at(2)), None);
+    }
+}
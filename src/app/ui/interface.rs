@@ -1,61 +1,201 @@
-use std::io;
+use std::fmt;
+use std::io::{self, Stdout, Write};
+use std::iter;
+use std::panic;
 use std::rc::Rc;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
+use termion::cursor;
 use termion::input::MouseTerminal;
-use termion::raw::IntoRawMode;
-use termion::screen::AlternateScreen;
+use termion::raw::{IntoRawMode, RawTerminal};
+use termion::screen::{AlternateScreen, ToMainScreen};
 use tui::backend::{Backend, TermionBackend};
+use tui::layout::Rect;
 use tui::Terminal;
 
-use super::{Context, Painter, TabBar, View};
-use crate::app::Config;
-use crate::Result;
+use super::{Context, Painter, Panel, Regions, TabBar, View};
+use crate::app::{time, Config, Logger};
+use crate::{Result, ResultExt};
+
+/// Same as the one written by `MouseTerminal` when dropped
+const EXIT_MOUSE_SEQUENCE: &str = "\x1b[?1006l\x1b[?1015l\x1b[?1002l\x1b[?1000l";
+
+/// Charts window can't be zoomed in to show less samples than that
+const MIN_WINDOW_SAMPLES: u32 = 8;
 
 #[allow(clippy::redundant_closure)]
-pub fn init(config: Arc<Config>, views: Vec<View>) -> Result<Interface<impl Backend>> {
+pub fn init(config: Arc<Config>, logger: &'static Logger, views: Vec<View>) -> Result<Interface<impl Backend>> {
     debug_assert!(!views.is_empty());
 
-    let stdout = io::stdout().into_raw_mode()?;
-    let stdout = MouseTerminal::from(stdout);
+    // Raw mode is held separately from the output stream, so it could be restored from the panic hook
+    let raw_terminal = io::stdout()
+        .into_raw_mode()
+        .context(|| "Unable to switch terminal into raw mode".to_string())?;
+    let raw_mode = RawMode(Arc::new(Mutex::new(raw_terminal)));
+    restore_on_panic(raw_mode.clone());
+
+    // Log records written to stderr would garble the alternate screen, they are available from the log pane instead
+    logger.set_stderr(false);
+
+    let stdout = MouseTerminal::from(io::stdout());
     let stdout = AlternateScreen::from(stdout);
     let backend = TermionBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
     terminal.hide_cursor()?;
 
+    let window = *config.history();
     let tab_titles = views.iter().map(|view| view.title()).collect::<Vec<_>>();
     let tabs = TabBar::new(tab_titles);
 
     Ok(Interface {
         config,
+        logger,
         terminal,
+        raw_mode,
         views,
         tabs,
+        paused: false,
+        cursor: None,
+        window,
+        mean_line: false,
+        panel: Panel::Charts,
+        log_offset: 0,
+        help: false,
+        regions: Regions::default(),
+        size: Rect::default(),
     })
 }
 
+/// Terminal raw mode, which is shared with the panic hook
+#[derive(Clone)]
+struct RawMode(Arc<Mutex<RawTerminal<Stdout>>>);
+
+impl RawMode {
+    fn restore(&self) {
+        // Lock might be poisoned already, but raw mode still needs to be restored
+        let raw_terminal = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Err(e) = raw_terminal.suspend_raw_mode() {
+            error!("Unable to restore terminal mode: {}", e);
+        }
+    }
+}
+
+impl fmt::Debug for RawMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("RawMode")
+    }
+}
+
+/// Install panic hook, which restores terminal before the panic message is printed,
+/// otherwise it is lost in the alternate screen and terminal is left in the raw mode
+fn restore_on_panic(raw_mode: RawMode) {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let mut stdout = io::stdout();
+        let _ = write!(stdout, "{}{}{}", EXIT_MOUSE_SEQUENCE, ToMainScreen, cursor::Show);
+        let _ = stdout.flush();
+        raw_mode.restore();
+
+        default_hook(info);
+    }));
+}
+
 /// Interface is a group tabs and tab contents
 #[derive(Debug)]
 pub struct Interface<B: Backend> {
     config: Arc<Config>,
+    logger: &'static Logger,
     terminal: Terminal<B>,
+    raw_mode: RawMode,
     views: Vec<View>,
     tabs: TabBar,
+    paused: bool,
+    // Chart cursor position, as a moment when the inspected sample was taken
+    cursor: Option<SystemTime>,
+    // Time period displayed by charts
+    window: Duration,
+    mean_line: bool,
+    // What is displayed in the right column
+    panel: Panel,
+    // Log pane scroll position, as an amount of records back from the latest one
+    log_offset: usize,
+    help: bool,
+    // Clickable areas from the last draw
+    regions: Regions,
+    // Terminal size at the last draw
+    size: Rect,
 }
 
 impl<B: Backend> Interface<B> {
     pub fn draw(&mut self) -> Result<()> {
+        // Titles are including the batteries state, which is changing on each update
+        self.tabs.set_titles(self.views.iter().map(View::title).collect());
+
+        let failure = self.failure();
+        let alerts = self.views.iter().map(View::alerts).sum();
+        // Records are copied only when they are going to be displayed
+        let log = if self.panel == Panel::Log {
+            self.logger.records()
+        } else {
+            vec![]
+        };
         let context = Rc::new(Context {
             tabs: &self.tabs,
             view: &self.views[self.tabs.index()],
+            paused: self.paused,
+            cursor: self.cursor,
+            window: self.window,
+            mean_line: self.mean_line,
+            panel: self.panel,
+            log: &log,
+            log_offset: self.log_offset,
+            failure: failure.as_ref().map(String::as_str),
+            alerts,
+            help: self.help,
         });
+        let mut regions = Regions::default();
         self.terminal.draw(|frame| {
-            Painter::from_context(context.clone()).draw(frame);
+            regions = Painter::from_context(context.clone()).draw(frame);
         })?;
+        self.regions = regions;
+        self.size = self.terminal.size()?;
 
         Ok(())
     }
 
+    /// Refresh failure of the selected battery or of any other one if selected is fine
+    fn failure(&self) -> Option<String> {
+        let selected = &self.views[self.tabs.index()];
+        let view = iter::once(selected)
+            .chain(self.views.iter())
+            .find(|view| view.is_stale())?;
+        let failure = view.failure()?;
+        let now = Instant::now();
+        let retry_in = if failure.retry_at > now {
+            failure.retry_at - now
+        } else {
+            Duration::from_secs(0)
+        };
+
+        Some(format!(
+            "{}; last known values from {} are displayed, retrying in {}s",
+            failure.error,
+            humantime::format_rfc3339_seconds(view.updated()),
+            retry_in.as_secs()
+        ))
+    }
+
+    /// Check if terminal was resized since the last draw
+    pub fn is_resized(&self) -> Result<bool> {
+        Ok(self.terminal.size()? != self.size)
+    }
+
+    pub fn views(&self) -> &[View] {
+        self.views.as_ref()
+    }
+
     pub fn views_mut(&mut self) -> &mut [View] {
         self.views.as_mut()
     }
@@ -63,4 +203,132 @@ impl<B: Backend> Interface<B> {
     pub fn tabs_mut(&mut self) -> &mut TabBar {
         &mut self.tabs
     }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        debug!("Updates are {}", if self.paused { "paused" } else { "resumed" });
+    }
+
+    /// Halve charts time window, but do not make it smaller than a few samples
+    pub fn zoom_in(&mut self) {
+        let minimum = *self.config.delay() * MIN_WINDOW_SAMPLES;
+        self.window = (self.window / 2).max(minimum).min(*self.config.history());
+        debug!("Charts window is zoomed in to {:?}", self.window);
+    }
+
+    /// Double charts time window, but do not make it larger than the kept history
+    pub fn zoom_out(&mut self) {
+        let history = *self.config.history();
+        self.window = self.window.checked_mul(2).unwrap_or(history).min(history);
+        debug!("Charts window is zoomed out to {:?}", self.window);
+    }
+
+    pub fn toggle_help(&mut self) {
+        self.help = !self.help;
+    }
+
+    pub fn toggle_mean_line(&mut self) {
+        self.mean_line = !self.mean_line;
+    }
+
+    /// Show `panel` instead of charts or switch back to charts if it is displayed already
+    pub fn toggle_panel(&mut self, panel: Panel) {
+        self.panel = if self.panel == panel { Panel::Charts } else { panel };
+        // Log pane is always opened at the latest records
+        self.log_offset = 0;
+
+        // Health trend requires whole stored history, so it is loaded only when needed
+        if self.panel == Panel::Health {
+            for view in self.views.iter_mut() {
+                view.load_health();
+            }
+        }
+    }
+
+    /// Move chart cursor one sample back in history, placing it at the latest sample first
+    pub fn cursor_back(&mut self) {
+        let chart = match self.views[self.tabs.index()].main_chart() {
+            Some(chart) => chart,
+            None => return,
+        };
+
+        self.cursor = match self.cursor {
+            // Cursor stays at the oldest sample if there is nothing before it
+            Some(moment) => chart.previous(moment).or(Some(moment)),
+            None => chart.latest(),
+        };
+    }
+
+    /// Move chart cursor one sample forward in history, hiding it when moving past the latest sample
+    pub fn cursor_forward(&mut self) {
+        let chart = self.views[self.tabs.index()].main_chart();
+        self.cursor = match (self.cursor, chart) {
+            (Some(moment), Some(chart)) => chart.next(moment),
+            _ => None,
+        };
+    }
+
+    /// Scroll log pane one record back to the older ones or forward to the newer ones
+    pub fn scroll_log(&mut self, back: bool) {
+        self.log_offset = if back {
+            (self.log_offset + 1).min(self.logger.count().saturating_sub(1))
+        } else {
+            self.log_offset.saturating_sub(1)
+        };
+    }
+
+    /// Select the clicked tab or move the chart cursor to the clicked moment
+    pub fn click(&mut self, x: u16, y: u16) {
+        if let Some(index) = self.regions.tab_at(x, y) {
+            self.tabs.select(index);
+        } else {
+            self.drag(x, y);
+        }
+    }
+
+    /// Move chart cursor to the sample under the mouse pointer
+    pub fn drag(&mut self, x: u16, y: u16) {
+        if let Some(position) = self.regions.chart_at(x, y) {
+            let age = time::seconds(self.window) * (1.0 - position);
+            let age = Duration::from_millis((age * 1000.0) as u64);
+            self.cursor = self.views[self.tabs.index()]
+                .main_chart()
+                .and_then(|chart| chart.moment(age));
+        }
+    }
+
+    /// Zoom charts in or out if mouse wheel is scrolled over them, or scroll log pane if it is displayed
+    pub fn scroll(&mut self, x: u16, y: u16, zoom_in: bool) {
+        if self.panel == Panel::Log {
+            if self.regions.is_panel_at(x, y) {
+                self.scroll_log(zoom_in);
+            }
+            return;
+        }
+        if self.regions.chart_at(x, y).is_none() {
+            return;
+        }
+
+        if zoom_in {
+            self.zoom_in();
+        } else {
+            self.zoom_out();
+        }
+    }
+}
+
+impl<B: Backend> Drop for Interface<B> {
+    fn drop(&mut self) {
+        // Panic hook is holding the raw mode too, so it should be removed first.
+        // Hook can't be changed while panicking, but it will restore terminal by itself in that case
+        if !thread::panicking() {
+            let _ = panic::take_hook();
+        }
+        self.raw_mode.restore();
+        self.logger.set_stderr(true);
+    }
 }
@@ -1,13 +1,18 @@
 mod chart;
+mod downsample;
+mod history;
 mod interface;
 mod painter;
+mod stats;
 mod tabs;
 mod units;
 mod view;
 
 pub use self::chart::{ChartData, ChartType};
+pub use self::history::History;
 pub use self::interface::{init, Interface};
-pub use self::painter::{Context, Painter};
+pub use self::painter::{Context, Painter, Panel, Regions};
+pub use self::stats::Statistics;
 pub use self::tabs::TabBar;
-pub use self::units::Units;
+pub use self::units::{CurrentUnit, EnergyUnit, PowerUnit, TemperatureUnit, Units};
 pub use self::view::View;
@@ -6,22 +6,23 @@
 ///           +------+------+----------+---------------------------------------------+  <------\
 /// SoC    →  |:::::::::: 65%          | Voltage graph                               |         |
 ///           +------------------------|                                             |         |
-/// Common    |                        | 33 % of the right column                    |         |
+/// Common    |                        | Charts are sharing right column equally     |         |
 /// info   →  | Vendor: …              |                                             |         |
 ///           | Model: …               |                                             |
 ///           | S/N: …                 |                                             |
 ///           +------------------------+---------------------------------------------+         m
 /// Energy    | Voltage: …             | Consumption graph                           |         a
 /// info   →  | Consumption: …         |                                             |         i
-///           +------------------------+ 33 % of the right column                    |         n
+///           +------------------------+                                             |         n
 /// Timings   | Time to full: …        |                                             |
 ///        →  | Time to empty: …       |                                             |         w
-///           +------------------------+                                             |         i
-/// Environ   | Temperature: …         |                                             |         n
-///        →  |                        +---------------------------------------------+         d
-///           |                        | Temperature graph                           |         o
-///           |                        |                                             |         w
-///           |                        | 33+1 % of the right column                  |
+///           | Estimated: …           |                                             |         i
+///           +------------------------+                                             |         n
+/// Environ   | Temperature: …         |                                             |         d
+///        →  |                        +---------------------------------------------+         o
+///           |                        | Temperature graph                           |         w
+///           |                        |                                             |
+///           |                        |                                             |         |
 ///           |                        |                                             |         |
 ///           |                        |                                             |         |
 ///           |                        |                                             |         |
@@ -32,32 +33,155 @@
 ///           \                                                                            /
 ///            \------------------ main window -------------------------------------------/
 /// ```
+///
+/// Narrow terminals are using a single column instead: charts are placed below the information
+/// if there is enough space left for them. Charts which do not fit the height are hidden.
+///
+/// Status bar at the bottom shows when data was refreshed, updates interval, mode indicators,
+/// alerts count and refresh failure, if any, while the last known values of the failed battery are greyed out.
+/// Help replaces the main window when requested.
 use std::borrow::Cow;
 use std::ops::Deref;
 use std::rc::Rc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use tui::backend::Backend;
 use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use tui::style::{Color, Modifier, Style};
-use tui::widgets::{Axis, Block, Borders, Chart, Dataset, Gauge, Marker, Paragraph, Row, Table, Tabs, Text, Widget};
+use tui::widgets::{
+    Axis, Block, Borders, Chart, Dataset, Gauge, List, Marker, Paragraph, Row, Table, Tabs, Text, Widget,
+};
 use tui::Frame;
+use unicode_width::UnicodeWidthStr;
 
 use battery::units::electric_potential::volt;
-use battery::units::energy::{joule, watt_hour};
+use battery::units::energy::watt_hour;
 use battery::units::power::watt;
 use battery::units::ratio::{percent, ratio};
-use battery::units::thermodynamic_temperature::{degree_celsius, kelvin};
+use battery::units::thermodynamic_temperature::kelvin;
 use battery::units::time::second;
 use battery::units::Unit;
 use battery::State;
 
-use super::{ChartData, TabBar, Units, View};
+use super::{downsample, ChartData, TabBar, View};
+use crate::app::time::{self, SECONDS_IN_DAY};
+
+/// Amount of points used to draw the chart cursor line
+const CURSOR_LINE_POINTS: u8 = 64;
+
+/// Amount of points used to draw the health replacement threshold line
+const THRESHOLD_LINE_POINTS: u8 = 64;
+
+/// Terminal narrower than this is using a single column layout
+const COMPACT_WIDTH: u16 = 80;
+
+/// Smallest terminal size the interface is drawn at
+const MIN_WIDTH: u16 = 30;
+const MIN_HEIGHT: u16 = 8;
+
+/// Height taken by the information blocks, including the state of charge bar
+const INFO_HEIGHT: u16 = 33;
+
+/// Smallest chart height, which fits its borders, X axis with labels and a few rows of data
+const MIN_CHART_HEIGHT: u16 = 7;
+
+/// Approximate width taken by chart borders, Y axis and its labels
+const CHART_DECORATIONS_WIDTH: u16 = 6;
+
+/// Hint displayed at the right side of the status bar
+const HELP_HINT: &str = " ? for help ";
+
+/// Keys and actions listed by help, same as in `Config` documentation
+const HELP: &[(&str, &str)] = &[
+    ("Left, Right", "move to previous or next tab"),
+    ("P, Space", "pause or resume updates"),
+    ("Comma, Period", "move the chart cursor back or forward in history"),
+    ("Plus, Minus", "zoom charts time window in or out"),
+    ("M", "show or hide charts mean value line"),
+    ("S", "show or hide charge and discharge sessions instead of charts"),
+    ("H", "show or hide battery health trend instead of charts"),
+    (
+        "L",
+        "show or hide log records instead of charts, Up and Down scroll them",
+    ),
+    ("R", "refresh batteries data right now"),
+    (
+        "Mouse",
+        "click tab to select it, scroll or drag over charts to zoom or move the cursor",
+    ),
+    ("?", "show or hide this help"),
+    ("Q, Ctrl+C, Esc", "close viewer"),
+];
+
+/// Content of the right column
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Panel {
+    Charts,
+    Sessions,
+    Health,
+    Log,
+}
+
+/// Screen areas of the elements which can be clicked, captured during the last draw
+#[derive(Debug, Default)]
+pub struct Regions {
+    tabs: Rect,
+    // Horizontal span of each tab title, end is exclusive
+    tab_spans: Vec<(u16, u16)>,
+    charts: Vec<Rect>,
+    panel: Rect,
+}
+
+impl Regions {
+    /// Index of the tab at the `x` and `y` terminal cell
+    pub fn tab_at(&self, x: u16, y: u16) -> Option<usize> {
+        if !contains(self.tabs, x, y) {
+            return None;
+        }
+
+        self.tab_spans.iter().position(|(start, end)| x >= *start && x < *end)
+    }
+
+    /// Check if the `x` and `y` terminal cell belongs to the right column panel
+    pub fn is_panel_at(&self, x: u16, y: u16) -> bool {
+        contains(self.panel, x, y)
+    }
+
+    /// Position of the `x` and `y` terminal cell on the chart time axis,
+    /// from `0.0` at the window start to `1.0` at the latest sample
+    pub fn chart_at(&self, x: u16, y: u16) -> Option<f64> {
+        let area = self.charts.iter().find(|area| contains(**area, x, y))?;
+        let width = area.width.saturating_sub(CHART_DECORATIONS_WIDTH);
+        if width == 0 {
+            return None;
+        }
+        let left = area.right().saturating_sub(width + 1);
+
+        Some((f64::from(x.saturating_sub(left)) / f64::from(width)).min(1.0))
+    }
+}
+
+fn contains(area: Rect, x: u16, y: u16) -> bool {
+    x >= area.left() && x < area.right() && y >= area.top() && y < area.bottom()
+}
 
 #[derive(Debug)]
 pub struct Context<'i> {
     pub tabs: &'i TabBar,
     pub view: &'i View,
+    pub paused: bool,
+    pub cursor: Option<SystemTime>,
+    pub window: Duration,
+    pub mean_line: bool,
+    pub panel: Panel,
+    // Log records, only available when log pane is displayed
+    pub log: &'i [String],
+    pub log_offset: usize,
+    // Refresh failure displayed in the status bar
+    pub failure: Option<&'i str>,
+    // Amount of alerts for all batteries
+    pub alerts: usize,
+    pub help: bool,
 }
 
 #[derive(Debug)]
@@ -68,29 +192,67 @@ impl<'i> Painter<'i> {
         Painter(context)
     }
 
-    pub fn draw<B: Backend>(&self, mut frame: Frame<B>) {
+    /// Draw the whole interface, returning the areas of the clickable elements
+    pub fn draw<B: Backend>(&self, mut frame: Frame<B>) -> Regions {
+        let size = frame.size();
+        if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
+            self.draw_too_small(&mut frame, size);
+            return Regions::default();
+        }
+
         let main = Layout::default()
             .direction(Direction::Vertical)
             .constraints(
                 [
                     Constraint::Length(3), // Tabs
-                    Constraint::Min(10),   // Main window
+                    Constraint::Min(0),    // Main window
+                    Constraint::Length(1), // Status bar
                 ]
                 .as_ref(),
             )
-            .split(frame.size());
-
-        // Left column with info and right column with graphs
-        let main_columns = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints(
-                [
-                    Constraint::Length(40), // Information
-                    Constraint::Min(20),    // Graphs
-                ]
-                .as_ref(),
-            )
-            .split(main[1]);
+            .split(size);
+
+        if self.help {
+            self.draw_tabs(&mut frame, main[0]);
+            self.draw_help(&mut frame, main[1]);
+            self.draw_status_bar(&mut frame, main[2]);
+
+            return Regions {
+                tabs: main[0],
+                tab_spans: self.tab_spans(main[0]),
+                ..Regions::default()
+            };
+        }
+
+        // Information goes to the left column and graphs to the right one,
+        // or below the information in a compact mode if there is enough space for them
+        let (info_area, panel_area) = if size.width >= COMPACT_WIDTH {
+            let main_columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(
+                    [
+                        Constraint::Length(40), // Information
+                        Constraint::Min(20),    // Graphs
+                    ]
+                    .as_ref(),
+                )
+                .split(main[1]);
+            (main_columns[0], Some(main_columns[1]))
+        } else if main[1].height >= INFO_HEIGHT + MIN_CHART_HEIGHT {
+            let main_rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(
+                    [
+                        Constraint::Length(INFO_HEIGHT), // Information
+                        Constraint::Min(0),              // Graphs
+                    ]
+                    .as_ref(),
+                )
+                .split(main[1]);
+            (main_rows[0], Some(main_rows[1]))
+        } else {
+            (main[1], None)
+        };
 
         // Percentage bar and information table
         let left_column = Layout::default()
@@ -99,26 +261,20 @@ impl<'i> Painter<'i> {
                 [
                     Constraint::Length(3),  // percentage bar
                     Constraint::Length(10), // common info
-                    Constraint::Length(9),  // energy stuff
-                    Constraint::Length(5),  // timings
+                    Constraint::Length(10), // energy stuff
+                    Constraint::Length(6),  // timings
                     Constraint::Min(4),     // environment
                 ]
                 .as_ref(),
             )
-            .split(main_columns[0]);
+            .split(info_area);
 
-        // Graphs
-        let right_column = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(
-                [
-                    Constraint::Percentage(33), // Voltage
-                    Constraint::Percentage(33), // Consumption
-                    Constraint::Percentage(34), // Temperature
-                ]
-                .as_ref(),
-            )
-            .split(main_columns[1]);
+        let mut regions = Regions {
+            tabs: main[0],
+            tab_spans: self.tab_spans(main[0]),
+            charts: vec![],
+            panel: panel_area.unwrap_or_default(),
+        };
 
         // Drawing all the things now!
         self.draw_tabs(&mut frame, main[0]);
@@ -127,17 +283,92 @@ impl<'i> Painter<'i> {
         self.draw_energy_info(&mut frame, left_column[2]);
         self.draw_timing_info(&mut frame, left_column[3]);
         self.draw_environment_info(&mut frame, left_column[4]);
-        self.draw_chart(&self.view.voltage(), &mut frame, right_column[0]);
-        self.draw_chart(&self.view.energy_rate(), &mut frame, right_column[1]);
-        self.draw_chart(&self.view.temperature(), &mut frame, right_column[2]);
+        self.draw_status_bar(&mut frame, main[2]);
+        match (self.panel, panel_area) {
+            (Panel::Charts, Some(area)) => regions.charts = self.draw_charts(&mut frame, area),
+            (Panel::Sessions, Some(area)) => self.draw_sessions(&mut frame, area),
+            (Panel::Health, Some(area)) => self.draw_health(&mut frame, area),
+            (Panel::Log, Some(area)) => self.draw_log(&mut frame, area),
+            (_, None) => trace!("Terminal is too small for {:?} panel, hiding it", self.panel),
+        }
+
+        regions
+    }
+
+    /// Draw as many charts as fit into `area`, each one is taking the same share of it.
+    ///
+    /// Returns areas taken by the charts drawn.
+    fn draw_charts<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) -> Vec<Rect> {
+        let fit = usize::from(area.height / MIN_CHART_HEIGHT);
+        let charts = &self.view.charts()[..self.view.charts().len().min(fit)];
+        if charts.is_empty() {
+            return vec![];
+        }
+
+        let areas = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                charts
+                    .iter()
+                    .map(|_| Constraint::Ratio(1, charts.len() as u32))
+                    .collect::<Vec<_>>(),
+            )
+            .split(area);
+        for (chart, area) in charts.iter().zip(areas.iter()) {
+            self.draw_chart(chart, frame, *area);
+        }
+
+        areas
+    }
+
+    fn draw_too_small<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
+        let text = [Text::Raw(Cow::from(format!(
+            "Terminal is too small: {}x{}\nat least {}x{} is required",
+            area.width, area.height, MIN_WIDTH, MIN_HEIGHT
+        )))];
+        // Message is placed in the middle of the screen
+        let message = Rect::new(
+            area.x,
+            area.y + area.height.saturating_sub(2) / 2,
+            area.width,
+            2.min(area.height),
+        );
+
+        Paragraph::new(text.iter())
+            .alignment(Alignment::Center)
+            .wrap(true)
+            .render(frame, message);
+    }
+
+    /// Horizontal span of each tab title, same as they are laid out by `Tabs` widget:
+    /// titles are padded with a space on both sides and separated by a one cell divider
+    fn tab_spans(&self, area: Rect) -> Vec<(u16, u16)> {
+        // Block borders are taking one cell
+        let mut x = area.left() + 1;
+        self.tabs
+            .titles()
+            .iter()
+            .map(|title| {
+                let end = x + title.width() as u16 + 2;
+                let span = (x, end);
+                x = end + 1;
+                span
+            })
+            .collect()
     }
 
     pub fn draw_tabs<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
+        // Note that spaces are intentional in here
+        let title = if self.paused {
+            " Batteries [paused] "
+        } else {
+            " Batteries "
+        };
         Tabs::default()
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title(" Batteries ") // Note that spaces are intentional in here
+                    .title(title)
                     .title_style(Style::default()),
             )
             .titles(self.tabs.titles())
@@ -166,8 +397,9 @@ impl<'i> Painter<'i> {
 
         let (gauge_area, text_area) = (chunks[0], chunks[1]);
 
-        // set text and gauge colors
+        // set text and gauge colors, last known values are greyed out
         let gauge_color = match () {
+            _ if self.view.is_stale() => Color::DarkGray,
             _ if value > 0.3 => Color::Green,
             _ if value > 0.15 => Color::Yellow,
             _ => Color::Red,
@@ -200,36 +432,340 @@ impl<'i> Painter<'i> {
     }
 
     pub fn draw_chart<B: Backend>(&self, data: &ChartData, frame: &mut Frame<B>, area: Rect) {
-        let title = format!(" {} ", data.title());
+        let statistics = data.statistics(self.window);
+        let title = match statistics {
+            Some(stats) if data.is_enabled() => format!(
+                " {} (min {:.2}, avg {:.2}, p95 {:.2}, max {:.2} {}) ",
+                data.title(),
+                stats.min,
+                stats.mean,
+                stats.p95,
+                stats.max,
+                data.y_title()
+            ),
+            _ => format!(" {} ", data.title()),
+        };
         let block = Block::default()
             .title(&title)
             .title_style(Style::default())
             .borders(Borders::ALL);
-        let value = data.current();
+        let cursor = self.cursor.and_then(|moment| data.sample(moment));
         // tui automatically hides chart legend if it's height is higher than `chart.height / 3`.
         // Since we have 3 charts already, legend will be invisible for most monitors,
         // so instead writing value as a X axis label
+        let value = match cursor {
+            Some(((_, y), timestamp)) => {
+                format!("{} at {}", data.format(y), humantime::format_rfc3339_seconds(timestamp))
+            }
+            None => data.current(),
+        };
+        let x_labels = data.x_labels(self.window);
         let x_axis: Axis<String> = Axis::default()
             .title(&value)
             .style(Style::default().fg(Color::Reset))
-            .bounds(data.x_bounds());
+            .labels(&x_labels)
+            .bounds(data.x_bounds(self.window));
         let y_labels = data.y_labels();
         let y_axis: Axis<String> = Axis::default()
             .title(data.y_title())
             .labels(&y_labels)
             .bounds(data.y_bounds());
 
+        // Cursor is drawn as a vertical line going through the whole chart height
+        let cursor_line = match cursor {
+            Some(((x, _), _)) => {
+                let [lower, upper] = data.y_bounds();
+                let step = (upper - lower) / f64::from(CURSOR_LINE_POINTS);
+                (0..=CURSOR_LINE_POINTS)
+                    .map(|i| (x, lower + step * f64::from(i)))
+                    .collect::<Vec<_>>()
+            }
+            None => vec![],
+        };
+        // Each terminal cell fits two Braille dots horizontally
+        let columns = usize::from(area.width.saturating_sub(CHART_DECORATIONS_WIDTH)) * 2;
+        let points = downsample::min_max(&data.points(self.window), data.x_bounds(self.window), columns);
+        let smoothed = downsample::min_max(&data.smoothed_points(self.window), data.x_bounds(self.window), columns);
+        // Mean value is drawn as a horizontal line going through the whole chart width
+        let mean_line = match statistics {
+            Some(stats) if self.mean_line && data.is_enabled() => {
+                let [lower, upper] = data.x_bounds(self.window);
+                let step = (upper - lower) / columns.max(1) as f64;
+                (0..=columns)
+                    .map(|i| (lower + step * i as f64, stats.mean))
+                    .collect::<Vec<_>>()
+            }
+            _ => vec![],
+        };
+
         Chart::default()
             .block(block)
             .x_axis(x_axis)
             .y_axis(y_axis)
-            .datasets(&[Dataset::default()
-                .marker(Marker::Braille)
-                .style(Style::default().fg(Color::Green))
-                .data(data.points())])
+            .datasets(&[
+                Dataset::default()
+                    .marker(Marker::Braille)
+                    .style(Style::default().fg(Color::Green))
+                    .data(&points),
+                Dataset::default()
+                    .marker(Marker::Braille)
+                    .style(Style::default().fg(Color::Magenta))
+                    .data(&smoothed),
+                Dataset::default()
+                    .marker(Marker::Braille)
+                    .style(Style::default().fg(Color::Blue))
+                    .data(&mean_line),
+                Dataset::default()
+                    .marker(Marker::Braille)
+                    .style(Style::default().fg(Color::Yellow))
+                    .data(&cursor_line),
+            ])
             .render(frame, area)
     }
 
+    fn draw_sessions<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
+        let block = Block::default()
+            .title(" Sessions ")
+            .title_style(Style::default())
+            .borders(Borders::ALL);
+        let sessions = self.view.sessions();
+        // Charge units are using the present voltage, as it is not stored for sessions
+        let voltage = f64::from(self.view.battery().voltage().get::<volt>());
+        let energy_units = self.view.config().energy_units();
+        let power_units = self.view.config().power_units();
+
+        let header = [
+            "State",
+            "Started (UTC)",
+            "Duration",
+            "State of charge",
+            "Energy",
+            "Avg / peak rate",
+        ];
+        let rows = sessions
+            .current()
+            .into_iter()
+            .chain(sessions.finished())
+            .map(|session| {
+                let (soc_start, soc_end) = session.state_of_charge();
+
+                vec![
+                    format!("{}", session.state()),
+                    time::timestamp(session.started()),
+                    time::minutes(session.duration()),
+                    format!("{:.0} % → {:.0} %", soc_start, soc_end),
+                    energy_units.format(session.energy(), voltage),
+                    format!(
+                        "{} / {}",
+                        power_units.format(session.average_rate()),
+                        power_units.format(session.peak_rate())
+                    ),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        Table::new(header.iter(), rows.iter().map(|row| Row::Data(row.iter())))
+            .header_style(Style::default().modifier(Modifier::BOLD))
+            .block(block)
+            .widths(&[11, 16, 10, 15, 12, 19])
+            .render(frame, area);
+    }
+
+    fn draw_status_bar<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(HELP_HINT.width() as u16)].as_ref())
+            .split(area);
+
+        // Only the time part of the timestamp is needed
+        let updated = humantime::format_rfc3339_seconds(self.view.updated()).to_string();
+        let mut text = vec![Text::raw(format!(
+            " Updated {} UTC, every {} ",
+            &updated[11..19],
+            humantime::format_duration(*self.view.config().delay())
+        ))];
+        let indicator = |label, color| Text::styled(label, Style::default().fg(Color::Black).bg(color));
+        if self.paused {
+            text.push(indicator(" PAUSED ", Color::Yellow));
+            text.push(Text::raw(" "));
+        }
+        // Samples are not taken while paused, so nothing is written either
+        if !self.paused && self.view.is_recording() {
+            text.push(indicator(" REC ", Color::Red));
+            text.push(Text::raw(" "));
+        }
+        // Charts cursor is replaying the history
+        if self.cursor.is_some() {
+            text.push(indicator(" REPLAY ", Color::Cyan));
+            text.push(Text::raw(" "));
+        }
+        if self.alerts > 0 {
+            let label = if self.alerts == 1 { "alert" } else { "alerts" };
+            text.push(Text::styled(
+                format!("{} {} ", self.alerts, label),
+                Style::default().fg(Color::Yellow).modifier(Modifier::BOLD),
+            ));
+        }
+        if let Some(failure) = self.failure {
+            text.push(Text::styled(failure, Style::default().fg(Color::Red)));
+        }
+
+        Paragraph::new(text.iter()).wrap(false).render(frame, chunks[0]);
+        Paragraph::new([Text::styled(HELP_HINT, Style::default().fg(Color::Cyan))].iter())
+            .wrap(false)
+            .render(frame, chunks[1]);
+    }
+
+    fn draw_help<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
+        let block = Block::default()
+            .title(" Help ")
+            .title_style(Style::default())
+            .borders(Borders::ALL);
+        let rows = HELP.iter().map(|(keys, action)| [*keys, *action]).collect::<Vec<_>>();
+
+        Table::new(["Keys", "Action"].iter(), rows.iter().map(|row| Row::Data(row.iter())))
+            .header_style(Style::default().modifier(Modifier::BOLD))
+            .block(block)
+            .widths(&[16, 64])
+            .render(frame, area);
+    }
+
+    /// Draw log records fitting the `area`, ending with the one at the scroll offset
+    fn draw_log<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
+        let title = if self.log_offset > 0 {
+            format!(" Log ({} newer records) ", self.log_offset)
+        } else {
+            " Log ".to_string()
+        };
+        let block = Block::default()
+            .title(&title)
+            .title_style(Style::default())
+            .borders(Borders::ALL);
+
+        let height = usize::from(area.height.saturating_sub(2));
+        let end = self.log.len().saturating_sub(self.log_offset);
+        let records = self.log[end.saturating_sub(height)..end].iter().map(|record| {
+            let style = match () {
+                _ if record.contains(" - ERROR - ") => Style::default().fg(Color::Red),
+                _ if record.contains(" - WARN - ") => Style::default().fg(Color::Yellow),
+                _ => Style::default(),
+            };
+            Text::styled(record.as_str(), style)
+        });
+
+        List::new(records).block(block).render(frame, area);
+    }
+
+    fn draw_health<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
+        let health = match self.view.health() {
+            Some(health) => health,
+            None => return,
+        };
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(10), Constraint::Min(3)].as_ref())
+            .split(area);
+
+        let block = Block::default()
+            .title(" Health trend ")
+            .title_style(Style::default())
+            .borders(Borders::LEFT | Borders::TOP | Borders::RIGHT);
+        let points = health.points();
+        let (first, last) = match (points.first(), points.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => {
+                let items = [["No stored samples yet", ""]];
+                self.draw_info_table(["Capacity", ""], &items, block, frame, chunks[0]);
+                return;
+            }
+        };
+
+        // Charge units are using the present voltage, as it is not stored for health points
+        let voltage = f64::from(self.view.battery().voltage().get::<volt>());
+        let energy_units = self.view.config().energy_units();
+        let design = &energy_units.format(last.energy_full_design(), voltage);
+        let full = &energy_units.format(last.energy_full(), voltage);
+        let state_of_health = &format!("{:.2} %", last.state_of_health());
+        let cycles = &match (first.cycle_count(), last.cycle_count()) {
+            (Some(first), Some(last)) => format!("{} → {}", first, last),
+            _ => "N/A".to_string(),
+        };
+        let fade = &match health.slope() {
+            Some(slope) => format!("{:+.3} % per week", slope * 7.0),
+            None => "N/A".to_string(),
+        };
+        let threshold_label = &format!("{:.0} % reached at", health.threshold());
+        let projected = &match health.projected() {
+            Some(day) => time::date(day),
+            None => "N/A".to_string(),
+        };
+        let tracked = &format!("{} days", points.len());
+
+        let items = vec![
+            ["Full design", design],
+            ["Last full", full],
+            ["State of health", state_of_health],
+            ["Cycles count", cycles],
+            ["Trend", fade],
+            [threshold_label, projected],
+            ["Tracked for", tracked],
+        ];
+        self.draw_info_table(["Capacity", ""], &items, block, frame, chunks[0]);
+
+        // Daily state of health chart, X axis is days since the last point
+        let chart_points = points
+            .iter()
+            .map(|point| {
+                let age = last.day().duration_since(point.day()).unwrap_or_default();
+                (-(age.as_secs() as f64) / SECONDS_IN_DAY as f64, point.state_of_health())
+            })
+            .collect::<Vec<_>>();
+        let days = chart_points.first().map(|(x, _)| -x).unwrap_or(0.0).max(1.0);
+        let threshold_line = (0..=THRESHOLD_LINE_POINTS)
+            .map(|i| {
+                (
+                    -days + days * f64::from(i) / f64::from(THRESHOLD_LINE_POINTS),
+                    health.threshold(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let lowest = chart_points.iter().map(|(_, y)| *y).fold(health.threshold(), f64::min);
+        let highest = chart_points.iter().map(|(_, y)| *y).fold(100.0, f64::max);
+        let y_bounds = [(lowest - 1.0).floor(), (highest + 1.0).ceil()];
+
+        let x_labels = [format!("-{:.0} days", days), "today".to_string()];
+        let y_labels = [format!("{:2.0}", y_bounds[0]), format!("{:2.0}", y_bounds[1])];
+        let block = Block::default()
+            .title(" State of health ")
+            .title_style(Style::default())
+            .borders(Borders::ALL);
+        Chart::default()
+            .block(block)
+            .x_axis(
+                Axis::default()
+                    .style(Style::default().fg(Color::Reset))
+                    .labels(&x_labels)
+                    .bounds([-days, 0.0]),
+            )
+            .y_axis(
+                Axis::default()
+                    .title(percent::abbreviation())
+                    .labels(&y_labels)
+                    .bounds(y_bounds),
+            )
+            .datasets(&[
+                Dataset::default()
+                    .marker(Marker::Braille)
+                    .style(Style::default().fg(Color::Red))
+                    .data(&threshold_line),
+                Dataset::default()
+                    .marker(Marker::Braille)
+                    .style(Style::default().fg(Color::Green))
+                    .data(&chart_points),
+            ])
+            .render(frame, chunks[1]);
+    }
+
     fn draw_common_info<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
         let block = Block::default()
             .title(" Information ") // Note that spaces are intentional
@@ -261,41 +797,27 @@ impl<'i> Painter<'i> {
         let battery = self.view.battery();
         let config = self.view.config();
 
-        let consumption = &format!("{:.2} {}", battery.energy_rate().get::<watt>(), watt::abbreviation());
-        let voltage = &format!("{:.2} {}", battery.voltage().get::<volt>(), volt::abbreviation());
+        let voltage = f64::from(battery.voltage().get::<volt>());
+        let energy_units = config.energy_units();
+
+        let consumption = &config.power_units().format(self.view.energy_rate());
+        let voltage_label = &format!("{:.2} {}", voltage, volt::abbreviation());
         let capacity = &format!(
             "{:.2} {}",
             battery.state_of_health().get::<percent>(),
             percent::abbreviation()
         );
-        let current = &match config.units() {
-            Units::Human => format!(
-                "{:.2} {}",
-                battery.energy().get::<watt_hour>(),
-                watt_hour::abbreviation()
-            ),
-            Units::Si => format!("{:.2} {}", battery.energy().get::<joule>(), joule::abbreviation()),
-        };
-        let last_full = &match config.units() {
-            Units::Human => format!(
-                "{:.2} {}",
-                battery.energy_full().get::<watt_hour>(),
-                watt_hour::abbreviation()
-            ),
-            Units::Si => format!("{:.2} {}", battery.energy_full().get::<joule>(), joule::abbreviation()),
-        };
-        let full_design = &match config.units() {
-            Units::Human => format!(
-                "{:.2} {}",
-                battery.energy_full_design().get::<watt_hour>(),
-                watt_hour::abbreviation()
-            ),
-            Units::Si => format!(
-                "{:.2} {}",
-                battery.energy_full_design().get::<joule>(),
-                joule::abbreviation()
-            ),
+        // Current is derived from the energy rate, since not all devices are reporting it
+        let current = &if voltage > 0.0 {
+            config
+                .current_units()
+                .format(f64::from(battery.energy_rate().get::<watt>()) / voltage)
+        } else {
+            "N/A".to_string()
         };
+        let energy = &energy_units.format(f64::from(battery.energy().get::<watt_hour>()), voltage);
+        let last_full = &energy_units.format(f64::from(battery.energy_full().get::<watt_hour>()), voltage);
+        let full_design = &energy_units.format(f64::from(battery.energy_full_design().get::<watt_hour>()), voltage);
         let consumption_label = match battery.state() {
             State::Charging => "Charging with",
             State::Discharging => "Discharging with",
@@ -304,9 +826,10 @@ impl<'i> Painter<'i> {
 
         let items = vec![
             [consumption_label, consumption],
-            ["Voltage", voltage],
-            ["Capacity", capacity],
+            ["Voltage", voltage_label],
             ["Current", current],
+            ["Capacity", capacity],
+            ["Energy", energy],
             ["Last full", last_full],
             ["Full design", full_design],
         ];
@@ -329,7 +852,23 @@ impl<'i> Painter<'i> {
             None => "N/A".to_string(),
         };
 
-        let items = vec![["Time to full", time_to_full], ["Time to empty", time_to_empty]];
+        // Estimation is not that precise anyway, so seconds are just a noise
+        let (estimate_label, estimate) = match self.view.estimate() {
+            Some(estimate) => (
+                match estimate.state {
+                    State::Charging => "Est. to full",
+                    _ => "Est. to empty",
+                },
+                format!("{} ({})", time::minutes(estimate.time), estimate.confidence),
+            ),
+            None => ("Estimated", "N/A".to_string()),
+        };
+
+        let items = vec![
+            ["Time to full", time_to_full],
+            ["Time to empty", time_to_empty],
+            [estimate_label, &estimate],
+        ];
         let header = ["Time", ""];
 
         self.draw_info_table(header, &items, block, frame, area);
@@ -341,10 +880,7 @@ impl<'i> Painter<'i> {
         let config = self.view.config();
 
         let temperature = &match battery.temperature() {
-            Some(temp) => match config.units() {
-                Units::Human => format!("{:.2} {}", temp.get::<degree_celsius>(), degree_celsius::abbreviation()),
-                Units::Si => format!("{:.2} {}", temp.get::<kelvin>(), kelvin::abbreviation()),
-            },
+            Some(temp) => config.temperature_units().format(f64::from(temp.get::<kelvin>())),
             None => "N/A".to_string(),
         };
 
@@ -373,9 +909,15 @@ impl<'i> Painter<'i> {
         // convert items to rows
         let rows = items.iter().map(|item| Row::Data(item.iter()));
 
-        // create table
+        // create table, last known values are greyed out
+        let style = if self.view.is_stale() {
+            Style::default().fg(Color::DarkGray)
+        } else {
+            Style::default()
+        };
         Table::new(header.iter(), rows)
-            .header_style(Style::default().modifier(Modifier::BOLD))
+            .style(style)
+            .header_style(style.modifier(Modifier::BOLD))
             .block(block)
             .widths(&[17, 17])
             .render(frame, area);
@@ -0,0 +1,84 @@
+use std::cmp::Ordering;
+
+/// Descriptive statistics for the chart values
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Statistics {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub p95: f64,
+}
+
+impl Statistics {
+    /// Calculate statistics for `values`, `None` is returned if there are no values at all
+    pub fn from_values<I>(values: I) -> Option<Statistics>
+    where
+        I: IntoIterator<Item = f64>,
+    {
+        let mut values = values.into_iter().collect::<Vec<_>>();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let sum: f64 = values.iter().sum();
+        // Nearest-rank method, so the percentile is always one of the values
+        let rank = (values.len() as f64 * 0.95).ceil() as usize;
+
+        Some(Statistics {
+            min: values[0],
+            max: values[values.len() - 1],
+            mean: sum / values.len() as f64,
+            p95: values[rank.max(1) - 1],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Statistics;
+
+    #[test]
+    fn empty() {
+        assert_eq!(Statistics::from_values(vec![]), None);
+    }
+
+    #[test]
+    fn single_value() {
+        let stats = Statistics::from_values(vec![4.0]).unwrap();
+
+        assert_eq!(
+            stats,
+            Statistics {
+                min: 4.0,
+                max: 4.0,
+                mean: 4.0,
+                p95: 4.0,
+            }
+        );
+    }
+
+    #[test]
+    fn unordered_values() {
+        let stats = Statistics::from_values(vec![3.0, 1.0, 2.0, 6.0]).unwrap();
+
+        assert_eq!(stats.min, 1.0);
+        assert_eq!(stats.max, 6.0);
+        assert_eq!(stats.mean, 3.0);
+    }
+
+    #[test]
+    fn p95_nearest_rank() {
+        // Rank is 95 for 100 values, which is the 95th smallest value
+        let stats = Statistics::from_values((1..=100).map(f64::from)).unwrap();
+        assert_eq!(stats.p95, 95.0);
+
+        // Rank is rounded up: 0.95 * 21 = 19.95, so it is the 20th value
+        let stats = Statistics::from_values((1..=21).map(f64::from)).unwrap();
+        assert_eq!(stats.p95, 20.0);
+
+        // Few values are making p95 the maximum
+        let stats = Statistics::from_values(vec![10.0, 30.0, 20.0]).unwrap();
+        assert_eq!(stats.p95, 30.0);
+    }
+}
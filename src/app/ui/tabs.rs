@@ -16,6 +16,13 @@ impl TabBar {
         self.index
     }
 
+    /// Select tab by its `index`, ignored if there is no such tab
+    pub fn select(&mut self, index: usize) {
+        if index < self.titles.len() {
+            self.index = index;
+        }
+    }
+
     pub fn next(&mut self) {
         self.index = (self.index + 1) % self.titles.len();
     }
@@ -28,6 +35,12 @@ impl TabBar {
         }
     }
 
+    /// Replace titles, e.g. when the batteries state changes
+    pub fn set_titles(&mut self, titles: Vec<String>) {
+        debug_assert_eq!(titles.len(), self.titles.len());
+        self.titles = titles;
+    }
+
     pub fn titles(&self) -> &[String] {
         self.titles.as_ref()
     }
@@ -1,7 +1,16 @@
 use std::str::FromStr;
 
+use battery::units::electric_charge::{ampere_hour, milliampere_hour};
+use battery::units::electric_current::{ampere, milliampere};
+use battery::units::energy::{joule, watt_hour};
+use battery::units::power::{milliwatt, watt};
+use battery::units::thermodynamic_temperature::{degree_celsius, degree_fahrenheit, kelvin};
+use battery::units::Unit;
+
+use crate::app::sample::{JOULES_IN_WATT_HOUR, ZERO_CELSIUS_IN_KELVIN};
 use crate::Error;
 
+/// Measurement units preset, which is used for quantities with no units chosen explicitly
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum Units {
     Human,
@@ -16,6 +25,20 @@ impl Units {
     pub fn arg_variants() -> [&'static str; 2] {
         ["human", "si"]
     }
+
+    pub fn energy(self) -> EnergyUnit {
+        match self {
+            Units::Human => EnergyUnit::WattHour,
+            Units::Si => EnergyUnit::Joule,
+        }
+    }
+
+    pub fn temperature(self) -> TemperatureUnit {
+        match self {
+            Units::Human => TemperatureUnit::Celsius,
+            Units::Si => TemperatureUnit::Kelvin,
+        }
+    }
 }
 
 impl FromStr for Units {
@@ -25,7 +48,225 @@ impl FromStr for Units {
         match () {
             _ if s.eq_ignore_ascii_case("human") => Ok(Units::Human),
             _ if s.eq_ignore_ascii_case("si") => Ok(Units::Si),
-            _ => Err(Error::ParseError),
+            _ => Err(Error::Parse("units", s.to_string())),
+        }
+    }
+}
+
+/// Energy units, including the charge ones used by batteries specifications,
+/// which are calculated from energy with the battery voltage
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum EnergyUnit {
+    WattHour,
+    Joule,
+    MilliampereHour,
+    AmpereHour,
+}
+
+impl EnergyUnit {
+    pub fn arg_variants() -> [&'static str; 4] {
+        ["wh", "j", "mah", "ah"]
+    }
+
+    /// Convert energy given in watt-hours, charge units are requiring `voltage` in volts
+    pub fn convert(self, value: f64, voltage: f64) -> Option<f64> {
+        match self {
+            EnergyUnit::WattHour => Some(value),
+            EnergyUnit::Joule => Some(value * JOULES_IN_WATT_HOUR),
+            EnergyUnit::MilliampereHour if voltage > 0.0 => Some(value / voltage * 1000.0),
+            EnergyUnit::AmpereHour if voltage > 0.0 => Some(value / voltage),
+            _ => None,
+        }
+    }
+
+    pub fn abbreviation(self) -> &'static str {
+        match self {
+            EnergyUnit::WattHour => watt_hour::abbreviation(),
+            EnergyUnit::Joule => joule::abbreviation(),
+            EnergyUnit::MilliampereHour => milliampere_hour::abbreviation(),
+            EnergyUnit::AmpereHour => ampere_hour::abbreviation(),
+        }
+    }
+
+    /// Decimal places displayed, small units do not need any
+    pub fn precision(self) -> usize {
+        match self {
+            EnergyUnit::Joule | EnergyUnit::MilliampereHour => 0,
+            EnergyUnit::WattHour | EnergyUnit::AmpereHour => 2,
+        }
+    }
+
+    /// Format energy value given in watt-hours, see `EnergyUnit::convert`
+    pub fn format(self, value: f64, voltage: f64) -> String {
+        match self.convert(value, voltage) {
+            Some(value) => format!("{:.*} {}", self.precision(), value, self.abbreviation()),
+            None => "N/A".to_string(),
+        }
+    }
+}
+
+impl FromStr for EnergyUnit {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match () {
+            _ if s.eq_ignore_ascii_case("wh") => Ok(EnergyUnit::WattHour),
+            _ if s.eq_ignore_ascii_case("j") => Ok(EnergyUnit::Joule),
+            _ if s.eq_ignore_ascii_case("mah") => Ok(EnergyUnit::MilliampereHour),
+            _ if s.eq_ignore_ascii_case("ah") => Ok(EnergyUnit::AmpereHour),
+            _ => Err(Error::Parse("energy units", s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum PowerUnit {
+    Watt,
+    Milliwatt,
+}
+
+impl PowerUnit {
+    pub fn arg_variants() -> [&'static str; 2] {
+        ["w", "mw"]
+    }
+
+    /// Convert power given in watts
+    pub fn convert(self, value: f64) -> f64 {
+        match self {
+            PowerUnit::Watt => value,
+            PowerUnit::Milliwatt => value * 1000.0,
+        }
+    }
+
+    pub fn abbreviation(self) -> &'static str {
+        match self {
+            PowerUnit::Watt => watt::abbreviation(),
+            PowerUnit::Milliwatt => milliwatt::abbreviation(),
+        }
+    }
+
+    /// Decimal places displayed, small units do not need any
+    pub fn precision(self) -> usize {
+        match self {
+            PowerUnit::Watt => 2,
+            PowerUnit::Milliwatt => 0,
+        }
+    }
+
+    /// Format power value given in watts
+    pub fn format(self, value: f64) -> String {
+        format!("{:.*} {}", self.precision(), self.convert(value), self.abbreviation())
+    }
+}
+
+impl FromStr for PowerUnit {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match () {
+            _ if s.eq_ignore_ascii_case("w") => Ok(PowerUnit::Watt),
+            _ if s.eq_ignore_ascii_case("mw") => Ok(PowerUnit::Milliwatt),
+            _ => Err(Error::Parse("power units", s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum CurrentUnit {
+    Ampere,
+    Milliampere,
+}
+
+impl CurrentUnit {
+    pub fn arg_variants() -> [&'static str; 2] {
+        ["a", "ma"]
+    }
+
+    /// Convert current given in amperes
+    pub fn convert(self, value: f64) -> f64 {
+        match self {
+            CurrentUnit::Ampere => value,
+            CurrentUnit::Milliampere => value * 1000.0,
+        }
+    }
+
+    pub fn abbreviation(self) -> &'static str {
+        match self {
+            CurrentUnit::Ampere => ampere::abbreviation(),
+            CurrentUnit::Milliampere => milliampere::abbreviation(),
+        }
+    }
+
+    /// Decimal places displayed, small units do not need any
+    pub fn precision(self) -> usize {
+        match self {
+            CurrentUnit::Ampere => 2,
+            CurrentUnit::Milliampere => 0,
+        }
+    }
+
+    /// Format current value given in amperes
+    pub fn format(self, value: f64) -> String {
+        format!("{:.*} {}", self.precision(), self.convert(value), self.abbreviation())
+    }
+}
+
+impl FromStr for CurrentUnit {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match () {
+            _ if s.eq_ignore_ascii_case("a") => Ok(CurrentUnit::Ampere),
+            _ if s.eq_ignore_ascii_case("ma") => Ok(CurrentUnit::Milliampere),
+            _ => Err(Error::Parse("current units", s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum TemperatureUnit {
+    Celsius,
+    Kelvin,
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    pub fn arg_variants() -> [&'static str; 3] {
+        ["c", "k", "f"]
+    }
+
+    /// Convert temperature given in kelvins
+    pub fn convert(self, value: f64) -> f64 {
+        match self {
+            TemperatureUnit::Celsius => value - ZERO_CELSIUS_IN_KELVIN,
+            TemperatureUnit::Kelvin => value,
+            TemperatureUnit::Fahrenheit => (value - ZERO_CELSIUS_IN_KELVIN) * 9.0 / 5.0 + 32.0,
+        }
+    }
+
+    pub fn abbreviation(self) -> &'static str {
+        match self {
+            TemperatureUnit::Celsius => degree_celsius::abbreviation(),
+            TemperatureUnit::Kelvin => kelvin::abbreviation(),
+            TemperatureUnit::Fahrenheit => degree_fahrenheit::abbreviation(),
+        }
+    }
+
+    /// Format temperature value given in kelvins
+    pub fn format(self, value: f64) -> String {
+        format!("{:.2} {}", self.convert(value), self.abbreviation())
+    }
+}
+
+impl FromStr for TemperatureUnit {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match () {
+            _ if s.eq_ignore_ascii_case("c") => Ok(TemperatureUnit::Celsius),
+            _ if s.eq_ignore_ascii_case("k") => Ok(TemperatureUnit::Kelvin),
+            _ if s.eq_ignore_ascii_case("f") => Ok(TemperatureUnit::Fahrenheit),
+            _ => Err(Error::Parse("temperature units", s.to_string())),
         }
     }
 }
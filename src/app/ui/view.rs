@@ -1,94 +1,368 @@
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
-use battery::units;
+use battery::units::energy::watt_hour;
+use battery::units::power::watt;
+use battery::units::ratio::percent;
+use battery::State;
 
-use super::{ChartData, ChartType, Units};
+use super::ChartData;
+use crate::app::device::Device;
+use crate::app::estimator::{Estimate, Estimator};
+use crate::app::filter::Filter;
+use crate::app::health::HealthTrend;
+use crate::app::sample::Sample;
+use crate::app::session::SessionTracker;
+use crate::app::store::Store;
+use crate::app::time;
 use crate::app::Config;
-use crate::Result;
+use crate::{Error, Result, ResultExt};
+
+/// State of charge below this percent is an alert while battery is discharging
+const LOW_STATE_OF_CHARGE: f32 = 15.0;
+
+/// Samples log size is checked for compaction once per that many stored samples
+const COMPACT_CHECK_SAMPLES: u64 = 3600;
+
+/// Failed battery refresh is retried with a doubling delay, but not rarer than that
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Battery refresh failure, which is retried later
+#[derive(Debug)]
+pub struct Failure {
+    pub error: String,
+    // Amount of the consecutive failed refreshes
+    pub attempts: u32,
+    pub retry_at: Instant,
+}
 
 /// View is a content of one separate tab - information about one specific battery
 #[derive(Debug)]
 pub struct View {
     config: Arc<Config>,
     battery: battery::Battery,
-    voltage: ChartData,
-    energy_rate: ChartData,
-    temperature: ChartData,
+    // Static part of the tab title, e.g. "45N1011 (BAT0)"
+    title: String,
+    charts: Vec<ChartData>,
+    filter: Option<Filter>,
+    // Energy rate in watts, filtered if filter is enabled
+    energy_rate: f64,
+    estimator: Estimator,
+    sessions: SessionTracker,
+    store: Option<Store>,
+    // Loaded on demand only
+    health: Option<HealthTrend>,
+    // Time of the last successful refresh
+    updated: SystemTime,
+    failure: Option<Failure>,
 }
 
 impl View {
-    pub fn new(config: Arc<Config>, battery: battery::Battery) -> View {
-        View {
-            config: config.clone(),
+    pub fn new(config: Arc<Config>, device: Device) -> View {
+        let title = View::base_title(&config, &device);
+        let store = if config.store() {
+            View::open_store(&device)
+        } else {
+            None
+        };
+        let battery = device.into_battery();
+        let charts = config
+            .charts()
+            .iter()
+            .map(|chart_type| ChartData::new(config.clone(), *chart_type))
+            .collect();
+
+        let filter = Filter::from_config(&config);
+        let energy_rate = f64::from(battery.energy_rate().get::<watt>());
+        let estimator = Estimator::new(*config.estimate_window());
+
+        let mut view = View {
+            config,
             battery,
-            voltage: ChartData::new(config.clone(), ChartType::Voltage),
-            energy_rate: ChartData::new(config.clone(), ChartType::EnergyRate),
-            temperature: ChartData::new(config, ChartType::Temperature),
+            title,
+            charts,
+            filter,
+            energy_rate,
+            estimator,
+            sessions: SessionTracker::default(),
+            store: None,
+            health: None,
+            updated: SystemTime::now(),
+            failure: None,
+        };
+        if let Some(store) = store {
+            view.restore(store);
         }
+
+        view
     }
 
-    /// Update internal state, but do not re-draw it
-    pub fn update(&mut self, manager: &mut battery::Manager) -> Result<()> {
-        manager.refresh(&mut self.battery)?;
+    fn open_store(device: &Device) -> Option<Store> {
+        match Store::for_device(device) {
+            Ok(store) => Some(store),
+            Err(e) => {
+                warn!("Unable to open samples store for battery: {}", e);
+                None
+            }
+        }
+    }
 
-        self.voltage
-            .push(self.battery.voltage().get::<units::electric_potential::volt>());
-        *self.voltage.battery_state() = self.battery.state();
+    /// Load the recent history from the battery samples `store` and keep it for the new samples
+    fn restore(&mut self, mut store: Store) {
+        if let Err(e) = store.compact() {
+            warn!("Unable to compact samples log {}: {}", store.path().display(), e);
+        }
 
-        self.energy_rate
-            .push(self.battery.energy_rate().get::<units::power::watt>());
-        *self.energy_rate.battery_state() = self.battery.state();
-
-        if let Some(temp) = self.battery.temperature() {
-            let value = match self.config.units() {
-                Units::Human => temp.get::<units::thermodynamic_temperature::degree_celsius>(),
-                Units::Si => temp.get::<units::thermodynamic_temperature::kelvin>(),
-            };
-            self.temperature.push(value);
-            *self.temperature.battery_state() = self.battery.state();
-            self.temperature.enabled(true);
-        } else {
-            self.temperature.enabled(false);
+        // Sessions might be long ago, e.g. before reboot, so they are restored from the whole log
+        match store.samples() {
+            Ok(samples) => {
+                for sample in samples {
+                    self.track_session(&sample);
+                }
+            }
+            Err(e) => warn!("Unable to load sessions from {}: {}", store.path().display(), e),
+        }
+
+        let since = time::earlier(SystemTime::now(), *self.config.history());
+        match store.load_since(since) {
+            Ok(samples) => {
+                debug!("Loaded {} samples from {}", samples.len(), store.path().display());
+                for sample in samples.iter() {
+                    self.record(sample);
+                }
+            }
+            Err(e) => warn!("Unable to load samples from {}: {}", store.path().display(), e),
+        }
+
+        self.store = Some(store);
+    }
+
+    /// Load battery health trend from the stored samples, if it was not loaded yet
+    pub fn load_health(&mut self) {
+        if self.health.is_some() {
+            return;
+        }
+
+        let mut health = HealthTrend::new(self.config.health_threshold());
+        if let Some(store) = self.store.as_ref() {
+            match store.samples() {
+                Ok(samples) => {
+                    for sample in samples {
+                        health.push(&sample);
+                    }
+                    debug!(
+                        "Loaded {} days of health trend from {}",
+                        health.points().len(),
+                        store.path().display()
+                    );
+                }
+                Err(e) => warn!("Unable to load samples from {}: {}", store.path().display(), e),
+            }
+        }
+
+        self.health = Some(health);
+    }
+
+    /// Refresh battery and update internal state, but do not re-draw it.
+    ///
+    /// Transient refresh errors are kept in the view, last known values are displayed meanwhile
+    /// and refresh is retried with an increasing delay, unless `force` is set.
+    /// Only fatal errors are returned.
+    pub fn update(&mut self, manager: &mut battery::Manager, force: bool) -> Result<()> {
+        let now = Instant::now();
+        match self.failure {
+            Some(ref failure) if !force && now < failure.retry_at => return Ok(()),
+            _ => {}
+        }
+
+        let title = &self.title;
+        match manager
+            .refresh(&mut self.battery)
+            .context(|| format!("Unable to refresh battery {}", title))
+        {
+            Ok(()) => {
+                if let Some(failure) = self.failure.take() {
+                    info!(
+                        "Battery {} is refreshed after {} failed attempts",
+                        self.title, failure.attempts
+                    );
+                }
+            }
+            Err(e) if e.is_transient() => {
+                self.fail(e, now);
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        }
+
+        self.updated = SystemTime::now();
+        let sample = Sample::new(self.updated, &self.battery);
+        self.record(&sample);
+        self.track_session(&sample);
+
+        if let Some(store) = self.store.as_mut() {
+            if let Err(e) = store.append(&sample) {
+                warn!("Unable to store sample into {}: {}", store.path().display(), e);
+            } else if store.appended() % COMPACT_CHECK_SAMPLES == 0 {
+                if let Err(e) = store.compact() {
+                    warn!("Unable to compact samples log {}: {}", store.path().display(), e);
+                }
+            }
         }
 
         Ok(())
     }
 
-    /// Return view title used in a tab header
-    pub fn title(&self) -> String {
-        if let Some(model) = self.battery.model() {
-            trace!("View is going to use battery model as a tab title: {}", model);
-            return model.to_string();
+    /// Keep refresh `error` and schedule the next attempt, doubling delay after each failure
+    fn fail(&mut self, error: Error, now: Instant) {
+        let attempts = self.failure.as_ref().map_or(0, |failure| failure.attempts) + 1;
+        let delay = *self.config.delay();
+        let retry_in = delay
+            .checked_mul(1 << (attempts - 1).min(16))
+            .unwrap_or(MAX_RETRY_DELAY)
+            .min(MAX_RETRY_DELAY)
+            .max(delay);
+        warn!("{}, retrying in {:?}", error, retry_in);
+
+        self.failure = Some(Failure {
+            error: error.to_string(),
+            attempts,
+            retry_at: now + retry_in,
+        });
+    }
+
+    /// Feed the `sample` to the consumers of the recent history: charts, estimator and so on
+    fn record(&mut self, sample: &Sample) {
+        let smoothed_rate = self
+            .filter
+            .as_mut()
+            .map(|filter| filter.apply(sample.timestamp, sample.energy_rate));
+        self.energy_rate = smoothed_rate.unwrap_or(sample.energy_rate);
+
+        for chart in self.charts.iter_mut() {
+            chart.update(sample, smoothed_rate);
         }
 
-        if let Some(vendor) = self.battery.vendor() {
-            trace!("View is going to use battery vendor as a tab title: {}", vendor);
-            return vendor.to_string();
+        if let Some(health) = self.health.as_mut() {
+            health.push(sample);
         }
+        self.estimator.push(sample.timestamp, sample.state, sample.energy);
+    }
+
+    fn track_session(&mut self, sample: &Sample) {
+        self.sessions.update(
+            sample.timestamp,
+            sample.state,
+            sample.state_of_charge,
+            sample.energy,
+            sample.energy_rate,
+            *self.config.delay(),
+        );
+    }
 
-        if let Some(sn) = self.battery.serial_number() {
-            trace!("View is going to use battery S/N as a tab title: {}", sn);
-            return sn.to_string();
+    /// Battery name, which is an alias chosen by user, model, vendor or serial number,
+    /// followed by the system name or index to tell apart the identical batteries
+    fn base_title(config: &Config, device: &Device) -> String {
+        let battery = device.battery();
+        let alias = battery.serial_number().and_then(|serial| config.alias(serial.trim()));
+        let name = match (alias, battery.model(), battery.vendor(), battery.serial_number()) {
+            (Some(alias), _, _, _) => alias,
+            (None, Some(model), _, _) => model,
+            (None, None, Some(vendor), _) => vendor,
+            (None, None, None, Some(serial)) => serial,
+            (None, None, None, None) => {
+                warn!("View is unable to determine proper tab title, falling back to unknown");
+                "Unknown battery"
+            }
+        };
+        let suffix = match device.name() {
+            Some(system_name) => system_name.to_string(),
+            None => format!("#{}", device.index()),
+        };
+
+        format!("{} ({})", name.trim(), suffix)
+    }
+
+    /// Return view title used in a tab header, e.g. "45N1011 (BAT0) 87% ⚡"
+    pub fn title(&self) -> String {
+        let state_of_charge = self.battery.state_of_charge().get::<percent>();
+        match self.battery.state() {
+            State::Charging => format!("{} {:.0}% ⚡", self.title, state_of_charge),
+            State::Discharging => format!("{} {:.0}% ▼", self.title, state_of_charge),
+            _ => format!("{} {:.0}%", self.title, state_of_charge),
         }
+    }
 
-        warn!("View is unable to determine proper tab title, falling back to unknown");
-        "Unknown battery".to_string()
+    /// Battery name without its current state, e.g. "45N1011 (BAT0)"
+    pub fn name(&self) -> &str {
+        &self.title
     }
 
     pub fn battery(&self) -> &battery::Battery {
         &self.battery
     }
 
-    pub fn voltage(&self) -> &ChartData {
-        &self.voltage
+    /// Energy rate in watts, filtered if filter is enabled
+    pub fn energy_rate(&self) -> f64 {
+        self.energy_rate
+    }
+
+    /// battop own estimation of time to empty or full
+    pub fn estimate(&self) -> Option<Estimate> {
+        self.estimator
+            .estimate(f64::from(self.battery.energy_full().get::<watt_hour>()))
+    }
+
+    pub fn sessions(&self) -> &SessionTracker {
+        &self.sessions
+    }
+
+    pub fn health(&self) -> Option<&HealthTrend> {
+        self.health.as_ref()
+    }
+
+    pub fn charts(&self) -> &[ChartData] {
+        self.charts.as_ref()
+    }
+
+    /// Chart with the longest history, which is used to move the chart cursor
+    pub fn main_chart(&self) -> Option<&ChartData> {
+        self.charts.iter().max_by_key(|chart| chart.len())
+    }
+
+    /// Time of the last successful refresh
+    pub fn updated(&self) -> SystemTime {
+        self.updated
+    }
+
+    /// Last refresh failure, if battery was not refreshed successfully since then
+    pub fn failure(&self) -> Option<&Failure> {
+        self.failure.as_ref()
+    }
+
+    /// Amount of battery conditions which need user attention: refresh failure,
+    /// low charge while discharging and state of health below the replacement threshold
+    pub fn alerts(&self) -> usize {
+        let state_of_charge = self.battery.state_of_charge().get::<percent>();
+        let state_of_health = self.battery.state_of_health().get::<percent>();
+        let low_charge = self.battery.state() == State::Discharging && state_of_charge < LOW_STATE_OF_CHARGE;
+        let worn_out = f64::from(state_of_health) < self.config.health_threshold();
+
+        [self.is_stale(), low_charge, worn_out]
+            .iter()
+            .filter(|alert| **alert)
+            .count()
     }
 
-    pub fn energy_rate(&self) -> &ChartData {
-        &self.energy_rate
+    /// Check if samples are written into the store, which requires it to be opened
+    /// and battery to be refreshed successfully
+    pub fn is_recording(&self) -> bool {
+        self.store.is_some() && !self.is_stale()
     }
 
-    pub fn temperature(&self) -> &ChartData {
-        &self.temperature
+    /// Check if the displayed values are the last known ones, as the battery can't be refreshed
+    pub fn is_stale(&self) -> bool {
+        self.failure.is_some()
     }
 
     pub fn config(&self) -> &Config {
@@ -10,11 +10,58 @@ pub type Result<T> = result::Result<T, Error>;
 pub enum Error {
     UserExit,
     NoBatteries,
+    BatteryNotFound(String),
+    Unavailable(String),
     Battery(battery::Error),
     Io(io::Error),
     Channel(mpsc::RecvError),
     Logger(log::SetLoggerError),
-    ParseError,
+    // Kind of the value expected and the value itself
+    Parse(&'static str, String),
+    // Description of the operation failed and its error
+    Context(String, Box<Error>),
+}
+
+impl Error {
+    /// Check if the failed operation might succeed when retried later.
+    ///
+    /// Battery errors are considered transient, as drivers and firmware might fail to respond once in a while
+    /// or the battery might be removed temporarily.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Battery(_) => true,
+            Error::Io(e) => match e.kind() {
+                io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => true,
+                _ => false,
+            },
+            Error::Context(_, e) => e.is_transient(),
+            _ => false,
+        }
+    }
+
+    /// What user could do to fix the error, if anything
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self {
+            Error::NoBatteries if cfg!(target_os = "linux") => {
+                Some("check if batteries are listed in the /sys/class/power_supply/ directory")
+            }
+            Error::NoBatteries => Some("check if batteries are reported by the system power settings"),
+            Error::BatteryNotFound(_) => Some("run `battop list` to see the batteries installed and their names"),
+            Error::Unavailable(_) => Some("not all batteries report every value, try another one"),
+            Error::Channel(_) => Some("this is a bug, please report it along with the log (see `--log-file`)"),
+            Error::Battery(e) => match error::Error::source(e).and_then(|e| e.downcast_ref::<io::Error>()) {
+                Some(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                    Some("check if the current user is allowed to read batteries information")
+                }
+                _ => None,
+            },
+            Error::Io(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                Some("check if the current user is allowed to access this file")
+            }
+            Error::Context(_, e) => e.suggestion(),
+            _ => None,
+        }
+    }
 }
 
 impl error::Error for Error {
@@ -24,6 +71,7 @@ impl error::Error for Error {
             Error::Io(e) => Some(e),
             Error::Channel(e) => Some(e),
             Error::Logger(e) => Some(e),
+            Error::Context(_, e) => Some(e.as_ref()),
             _ => None,
         }
     }
@@ -34,11 +82,14 @@ impl fmt::Display for Error {
         match self {
             Error::UserExit => f.write_str("User-requested exit"),
             Error::NoBatteries => f.write_str("Unable to find any batteries installed"),
-            Error::ParseError => f.write_str("Unable to parse value"),
+            Error::BatteryNotFound(selector) => write!(f, "Unable to find battery matching \"{}\"", selector),
+            Error::Unavailable(what) => write!(f, "{} is not available for this battery", what),
+            Error::Parse(kind, value) => write!(f, "\"{}\" is not a valid {}", value, kind),
             Error::Battery(e) => fmt::Display::fmt(e, f),
             Error::Io(e) => fmt::Display::fmt(e, f),
-            Error::Channel(e) => fmt::Display::fmt(e, f),
+            Error::Channel(_) => f.write_str("Events thread had stopped unexpectedly"),
             Error::Logger(e) => fmt::Display::fmt(e, f),
+            Error::Context(context, e) => write!(f, "{}: {}", context, e),
         }
     }
 }
@@ -66,3 +117,22 @@ impl From<log::SetLoggerError> for Error {
         Error::Logger(e)
     }
 }
+
+/// Adds the failed operation description to the errors
+pub trait ResultExt<T> {
+    fn context<F>(self, f: F) -> Result<T>
+    where
+        F: FnOnce() -> String;
+}
+
+impl<T, E> ResultExt<T> for result::Result<T, E>
+where
+    E: Into<Error>,
+{
+    fn context<F>(self, f: F) -> Result<T>
+    where
+        F: FnOnce() -> String,
+    {
+        self.map_err(|e| Error::Context(f(), Box::new(e.into())))
+    }
+}
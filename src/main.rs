@@ -3,35 +3,69 @@ extern crate structopt;
 #[macro_use]
 extern crate log;
 
+use std::path::PathBuf;
+use std::process;
 use std::sync::Arc;
 
 use structopt::StructOpt;
 
+use self::app::config::Command;
+
 mod app;
 mod errors;
 
-pub use self::errors::{Error, Result};
+pub use self::errors::{Error, Result, ResultExt};
 
-fn main() -> Result<()> {
+fn main() {
     let config = Arc::new(app::config::Config::from_args());
-    stderrlog::new()
-        .module(module_path!())
-        .verbosity(config.verbosity())
-        .timestamp(stderrlog::Timestamp::Second)
-        .init()?;
+    let logger = match app::Logger::init(module_path!(), &config) {
+        Ok(logger) => logger,
+        Err(e) => exit_with(&e),
+    };
 
     trace!("Starting with {:?}", &config);
-    let mut app = app::init(config)?;
+    let result = match config.command() {
+        Command::Tui => app::init(config.clone(), logger).and_then(|mut app| app.run()),
+        Command::List => app::list(&config),
+        Command::Get {
+            battery,
+            field,
+        } => app::get(&config, battery, *field),
+        Command::Watch {
+            fields,
+        } => app::watch(&config, fields),
+        Command::Export {
+            battery,
+            since,
+            output,
+        } => app::export(
+            &config,
+            battery.as_ref().map(String::as_str),
+            *since,
+            output.as_ref().map(PathBuf::as_path),
+        ),
+        Command::Report {
+            format,
+            output,
+        } => app::report(&config, *format, output.as_ref().map(PathBuf::as_path)),
+    };
 
-    match app.run() {
-        Err(Error::UserExit) => {
-            trace!("Exit was requested by user, terminating");
-            Ok(())
-        }
-        Ok(_) => Ok(()),
+    match result {
+        Err(Error::UserExit) => trace!("Exit was requested by user, terminating"),
+        Ok(_) => {}
         Err(e) => {
-            error!("Error occurred: {:?}", e);
-            Err(e)
+            debug!("Error occurred: {:?}", e);
+            exit_with(&e)
         }
     }
 }
+
+/// Print human-readable error message along with a suggestion how to fix it and exit
+fn exit_with(e: &Error) -> ! {
+    eprintln!("Error: {}", e);
+    if let Some(suggestion) = e.suggestion() {
+        eprintln!("Hint: {}", suggestion);
+    }
+
+    process::exit(1)
+}